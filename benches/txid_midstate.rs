@@ -0,0 +1,59 @@
+// Compares naive txids with txids resumed from a shared-prefix midstate.
+// Run with `cargo bench`; libtest's bench harness needs nightly, so this
+// times the loops directly.
+use std::hint::black_box;
+use std::time::Instant;
+
+use rust_week_4_exercises::hashes::Sha256Midstate;
+use rust_week_4_exercises::{LegacyTransaction, OutPoint, TxInput, TxOutput};
+
+const ITERATIONS: u32 = 20_000;
+
+fn main() {
+    // Many payouts spending the same ten inputs, differing only in outputs
+    let mut builder = LegacyTransaction::builder().version(2);
+    for vout in 0..10 {
+        builder = builder.add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout,
+            },
+            script_sig: vec![0x51; 107],
+            sequence: 0xFFFFFFFD,
+        });
+    }
+    let shared = builder.build();
+    let txs: Vec<LegacyTransaction> = (0..100u64)
+        .map(|i| {
+            let mut tx = shared.clone();
+            tx.outputs.push(TxOutput {
+                value: 10_000 + i,
+                script_pubkey: vec![0x00, 0x14, i as u8],
+            });
+            tx
+        })
+        .collect();
+
+    // Everything up to and including the inputs is common to all of them
+    let prefix_len = shared.to_bytes().len() - 5;
+    let midstate = Sha256Midstate::new(&shared.to_bytes()[..prefix_len]);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS / 100 {
+        for tx in &txs {
+            black_box(tx.txid());
+        }
+    }
+    let naive = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS / 100 {
+        for tx in &txs {
+            black_box(tx.txid_with_prefix_cache(&midstate));
+        }
+    }
+    let cached = start.elapsed();
+
+    println!("naive txid:    {naive:?} for {ITERATIONS} transactions");
+    println!("midstate txid: {cached:?} for {ITERATIONS} transactions");
+}
@@ -0,0 +1,249 @@
+use std::{fmt, str::FromStr};
+
+use crate::script::ScriptType;
+use crate::{BitcoinError, base58, bech32, script};
+
+// Network parameters used for address encoding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Signet,
+    Regtest,
+    // Custom signets or other chains with their own prefixes
+    Custom {
+        p2pkh_version: u8,
+        p2sh_version: u8,
+        bech32_hrp: String,
+    },
+}
+
+impl Network {
+    pub fn p2pkh_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet | Network::Signet | Network::Regtest => 0x6f,
+            Network::Custom { p2pkh_version, .. } => *p2pkh_version,
+        }
+    }
+
+    pub fn p2sh_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x05,
+            Network::Testnet | Network::Signet | Network::Regtest => 0xc4,
+            Network::Custom { p2sh_version, .. } => *p2sh_version,
+        }
+    }
+
+    pub fn bech32_hrp(&self) -> &str {
+        match self {
+            Network::Mainnet => "bc",
+            Network::Testnet | Network::Signet => "tb",
+            Network::Regtest => "bcrt",
+            Network::Custom { bech32_hrp, .. } => bech32_hrp,
+        }
+    }
+}
+
+pub fn p2pkh_address(pubkey_hash: &[u8; 20], network: &Network) -> String {
+    let mut payload = vec![network.p2pkh_version()];
+    payload.extend_from_slice(pubkey_hash);
+    base58::encode_check(&payload)
+}
+
+pub fn p2sh_address(script_hash: &[u8; 20], network: &Network) -> String {
+    let mut payload = vec![network.p2sh_version()];
+    payload.extend_from_slice(script_hash);
+    base58::encode_check(&payload)
+}
+
+pub fn p2wpkh_address(pubkey_hash: &[u8; 20], network: &Network) -> String {
+    bech32::encode_segwit(network.bech32_hrp(), 0, pubkey_hash)
+}
+
+pub fn p2wsh_address(script_hash: &[u8; 32], network: &Network) -> String {
+    bech32::encode_segwit(network.bech32_hrp(), 0, script_hash)
+}
+
+pub fn p2tr_address(output_key: &[u8; 32], network: &Network) -> String {
+    bech32::encode_segwit(network.bech32_hrp(), 1, output_key)
+}
+
+// Decode an address for `network` into the scriptPubKey it pays to
+pub fn address_to_script_pubkey(address: &str, network: &Network) -> Result<Vec<u8>, BitcoinError> {
+    let hrp_prefix = format!("{}1", network.bech32_hrp());
+    if address.to_lowercase().starts_with(&hrp_prefix) {
+        let (version, program) = bech32::decode_segwit(network.bech32_hrp(), address)?;
+        return Ok(script::witness_script(version, &program));
+    }
+
+    let payload = base58::decode_check(address)?;
+    let hash: [u8; 20] = payload
+        .get(1..)
+        .and_then(|hash| hash.try_into().ok())
+        .ok_or_else(|| BitcoinError::ParseError("Invalid base58 address length".to_string()))?;
+
+    match payload[0] {
+        version if version == network.p2pkh_version() => Ok(script::p2pkh_script(&hash)),
+        version if version == network.p2sh_version() => Ok(script::p2sh_script(&hash)),
+        version => Err(BitcoinError::ParseError(format!(
+            "Address version byte 0x{version:02x} does not match the network"
+        ))),
+    }
+}
+
+// Address an output script pays to, if it follows a standard addressable template
+pub fn script_to_address(script_pubkey: &[u8], network: &Network) -> Option<String> {
+    match script::classify_script(script_pubkey) {
+        ScriptType::P2PKH => Some(p2pkh_address(
+            script_pubkey[3..23].try_into().ok()?,
+            network,
+        )),
+        ScriptType::P2SH => Some(p2sh_address(script_pubkey[2..22].try_into().ok()?, network)),
+        ScriptType::P2WPKH => Some(p2wpkh_address(
+            script_pubkey[2..22].try_into().ok()?,
+            network,
+        )),
+        ScriptType::P2WSH => Some(p2wsh_address(
+            script_pubkey[2..34].try_into().ok()?,
+            network,
+        )),
+        ScriptType::P2TR => Some(p2tr_address(script_pubkey[2..34].try_into().ok()?, network)),
+        _ => None,
+    }
+}
+
+// Canonical form of an address for comparisons: lowercase Bech32/Bech32m, or
+// Base58Check re-encoded from its payload. BIP-173 forbids mixed case, so such
+// addresses are rejected rather than lowercased.
+pub fn normalize_address(addr: &str) -> Result<String, BitcoinError> {
+    Ok(addr.trim().parse::<Address>()?.to_string())
+}
+
+// Decoded address of any supported format. Fields are private so the payload
+// length always matches the script type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    network: Network,
+    script_type: ScriptType,
+    // Hash for base58 addresses, witness program for segwit ones
+    payload: Vec<u8>,
+}
+
+impl Address {
+    // Fails unless `payload` is the 20-byte hash or 20/32-byte witness program
+    // that `script_type` requires
+    pub fn new(
+        network: Network,
+        script_type: ScriptType,
+        payload: Vec<u8>,
+    ) -> Result<Self, BitcoinError> {
+        let expected_len = match script_type {
+            ScriptType::P2PKH | ScriptType::P2SH | ScriptType::P2WPKH => 20,
+            ScriptType::P2WSH | ScriptType::P2TR => 32,
+            _ => {
+                return Err(BitcoinError::ParseError(format!(
+                    "{script_type:?} has no address form"
+                )));
+            }
+        };
+        if payload.len() != expected_len {
+            return Err(BitcoinError::ParseError(format!(
+                "{script_type:?} address payload must be {expected_len} bytes, got {}",
+                payload.len()
+            )));
+        }
+        Ok(Address {
+            network,
+            script_type,
+            payload,
+        })
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    pub fn script_type(&self) -> ScriptType {
+        self.script_type
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn to_script_pubkey(&self) -> Vec<u8> {
+        match self.script_type {
+            // `new` guarantees 20 bytes for both base58 types
+            ScriptType::P2PKH => {
+                script::p2pkh_script(self.payload[..].try_into().expect("20 byte hash"))
+            }
+            ScriptType::P2SH => {
+                script::p2sh_script(self.payload[..].try_into().expect("20 byte hash"))
+            }
+            ScriptType::P2TR => script::witness_script(1, &self.payload),
+            _ => script::witness_script(0, &self.payload),
+        }
+    }
+}
+
+impl FromStr for Address {
+    type Err = BitcoinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        let hrp_network = [
+            ("bcrt", Network::Regtest),
+            ("bc", Network::Mainnet),
+            ("tb", Network::Testnet),
+        ]
+        .into_iter()
+        .find(|(hrp, _)| {
+            lower
+                .strip_prefix(hrp)
+                .is_some_and(|rest| rest.starts_with('1'))
+        });
+
+        if let Some((hrp, network)) = hrp_network {
+            let (version, program) = bech32::decode_segwit(hrp, s)?;
+            let script_type = match (version, program.len()) {
+                (0, 20) => ScriptType::P2WPKH,
+                (0, 32) => ScriptType::P2WSH,
+                (1, 32) => ScriptType::P2TR,
+                _ => {
+                    return Err(BitcoinError::ParseError(format!(
+                        "Unsupported witness version {version} program"
+                    )));
+                }
+            };
+            return Address::new(network, script_type, program);
+        }
+
+        let payload = base58::decode_check(s)?;
+        if payload.len() != 21 {
+            return Err(BitcoinError::ParseError(
+                "Invalid base58 address length".to_string(),
+            ));
+        }
+        let (network, script_type) = match payload[0] {
+            0x00 => (Network::Mainnet, ScriptType::P2PKH),
+            0x05 => (Network::Mainnet, ScriptType::P2SH),
+            0x6f => (Network::Testnet, ScriptType::P2PKH),
+            0xc4 => (Network::Testnet, ScriptType::P2SH),
+            version => {
+                return Err(BitcoinError::ParseError(format!(
+                    "Unknown address version byte 0x{version:02x}"
+                )));
+            }
+        };
+        Address::new(network, script_type, payload[1..].to_vec())
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let encoded =
+            script_to_address(&self.to_script_pubkey(), &self.network).ok_or(fmt::Error)?;
+        f.write_str(&encoded)
+    }
+}
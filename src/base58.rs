@@ -0,0 +1,80 @@
+use crate::BitcoinError;
+use crate::hashes::sha256d;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+pub fn encode(data: &[u8]) -> String {
+    // Each leading zero byte is represented by a leading '1'
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+
+    // Base-58 digits, least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut encoded = String::with_capacity(zeros + digits.len());
+    encoded.extend(std::iter::repeat_n('1', zeros));
+    encoded.extend(digits.iter().rev().map(|&d| ALPHABET[d as usize] as char));
+    encoded
+}
+
+pub fn decode(s: &str) -> Result<Vec<u8>, BitcoinError> {
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+
+    // Bytes, least significant first
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len());
+    for c in s.bytes().skip(zeros) {
+        let value = ALPHABET.iter().position(|&a| a == c).ok_or_else(|| {
+            BitcoinError::ParseError(format!("Invalid base58 character '{}'", c as char))
+        })?;
+
+        let mut carry = value as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut decoded = vec![0; zeros];
+    decoded.extend(bytes.iter().rev());
+    Ok(decoded)
+}
+
+// Base58 with a 4-byte double SHA-256 checksum appended
+pub fn encode_check(payload: &[u8]) -> String {
+    let mut data = payload.to_vec();
+    data.extend_from_slice(&sha256d(payload)[..4]);
+    encode(&data)
+}
+
+pub fn decode_check(s: &str) -> Result<Vec<u8>, BitcoinError> {
+    let mut data = decode(s)?;
+    if data.len() < 4 {
+        return Err(BitcoinError::ParseError(
+            "Base58Check data too short".to_string(),
+        ));
+    }
+
+    let checksum = data.split_off(data.len() - 4);
+    if sha256d(&data)[..4] != checksum[..] {
+        return Err(BitcoinError::ParseError(
+            "Invalid Base58Check checksum".to_string(),
+        ));
+    }
+    Ok(data)
+}
@@ -0,0 +1,52 @@
+// Standard base64 (RFC 4648) with padding, as used for PSBTs
+
+use crate::BitcoinError;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &byte)| acc | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+pub fn decode(s: &str) -> Result<Vec<u8>, BitcoinError> {
+    let invalid = |reason: &str| BitcoinError::ParseError(format!("Invalid base64: {reason}"));
+
+    let bytes = s.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return Err(invalid("length is not a multiple of 4"));
+    }
+
+    let mut decoded = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (index, chunk) in bytes.chunks(4).enumerate() {
+        let is_last = index == bytes.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return Err(invalid("misplaced padding"));
+        }
+
+        let mut group = 0u32;
+        for (i, &c) in chunk[..4 - padding].iter().enumerate() {
+            let value = ALPHABET
+                .iter()
+                .position(|&x| x == c)
+                .ok_or_else(|| invalid("character outside the alphabet"))?;
+            group |= (value as u32) << (18 - 6 * i);
+        }
+        decoded.extend(&group.to_be_bytes()[1..4 - padding]);
+    }
+    Ok(decoded)
+}
@@ -0,0 +1,144 @@
+// Bech32 (BIP-173) and Bech32m (BIP-350) encoding of segwit addresses
+
+use crate::BitcoinError;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+fn polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+
+    let mut chk: u32 = 1;
+    for &value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ value as u32;
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|b| b & 0x1f));
+    expanded
+}
+
+// Regroup bits, e.g. 8-bit bytes into 5-bit words
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return None;
+        }
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+// Encode a witness program as a segwit address, using Bech32 for version 0
+// and Bech32m for later versions
+pub fn encode_segwit(hrp: &str, witness_version: u8, program: &[u8]) -> String {
+    let hrp = hrp.to_lowercase();
+
+    let mut data = vec![witness_version];
+    data.extend(convert_bits(program, 8, 5, true).expect("bytes fit in 8 bits"));
+
+    let constant = if witness_version == 0 {
+        BECH32_CONST
+    } else {
+        BECH32M_CONST
+    };
+    let mut values = hrp_expand(&hrp);
+    values.extend(&data);
+    values.extend([0; 6]);
+    let checksum = polymod(&values) ^ constant;
+
+    let mut encoded = hrp;
+    encoded.push('1');
+    encoded.extend(data.iter().map(|&d| CHARSET[d as usize] as char));
+    encoded.extend((0..6).map(|i| CHARSET[((checksum >> (5 * (5 - i))) & 0x1f) as usize] as char));
+    encoded
+}
+
+// Decode a segwit address for the expected `hrp` into its witness version and program
+pub fn decode_segwit(hrp: &str, address: &str) -> Result<(u8, Vec<u8>), BitcoinError> {
+    let invalid =
+        |reason: &str| BitcoinError::ParseError(format!("Invalid bech32 address: {reason}"));
+
+    if address.chars().any(|c| c.is_ascii_lowercase())
+        && address.chars().any(|c| c.is_ascii_uppercase())
+    {
+        return Err(invalid("mixed case"));
+    }
+    let address = address.to_lowercase();
+
+    let (addr_hrp, data_part) = address
+        .rsplit_once('1')
+        .ok_or_else(|| invalid("missing separator"))?;
+    if addr_hrp != hrp.to_lowercase() {
+        return Err(invalid("wrong human-readable part"));
+    }
+    if data_part.len() < 7 {
+        return Err(invalid("too short"));
+    }
+
+    let data = data_part
+        .bytes()
+        .map(|c| CHARSET.iter().position(|&x| x == c).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid("character outside the bech32 charset"))?;
+
+    let witness_version = data[0];
+    if witness_version > 16 {
+        return Err(invalid("witness version above 16"));
+    }
+    let constant = if witness_version == 0 {
+        BECH32_CONST
+    } else {
+        BECH32M_CONST
+    };
+    let mut values = hrp_expand(addr_hrp);
+    values.extend(&data);
+    if polymod(&values) != constant {
+        return Err(invalid("bad checksum"));
+    }
+
+    let program = convert_bits(&data[1..data.len() - 6], 5, 8, false)
+        .ok_or_else(|| invalid("bad padding"))?;
+    if !(2..=40).contains(&program.len())
+        || (witness_version == 0 && program.len() != 20 && program.len() != 32)
+    {
+        return Err(invalid("bad witness program length"));
+    }
+    Ok((witness_version, program))
+}
@@ -0,0 +1,196 @@
+use std::str::FromStr;
+
+use crate::hashes::{hash160, hmac_sha512};
+use crate::secp256k1::{CURVE_ORDER, U256};
+use crate::{BitcoinError, Point, base58};
+
+// Length of a serialized extended key payload (without checksum)
+const EXTENDED_KEY_LEN: usize = 78;
+
+// Child indices at or above this value are hardened
+pub const HARDENED_INDEX: u32 = 1 << 31;
+
+// First four bytes of a key's hash160, identifying it as a parent or master key
+pub type Fingerprint = [u8; 4];
+
+// Sequence of child indices from a master key, hardened indices included
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DerivationPath(pub Vec<u32>);
+
+impl DerivationPath {
+    // Parse the little-endian u32 per level encoding used in PSBT key origins
+    pub fn from_bytes(data: &[u8]) -> Result<Self, BitcoinError> {
+        if !data.len().is_multiple_of(4) {
+            return Err(BitcoinError::ParseError(format!(
+                "Derivation path length {} is not a multiple of 4",
+                data.len()
+            )));
+        }
+
+        Ok(DerivationPath(
+            data.chunks_exact(4)
+                .map(|level| u32::from_le_bytes(level.try_into().expect("4 byte chunk")))
+                .collect(),
+        ))
+    }
+
+    // Four little-endian bytes per level, hardened bit included
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0
+            .iter()
+            .flat_map(|level| level.to_le_bytes())
+            .collect()
+    }
+}
+
+// Parse paths like "m/84'/0'/0'/0/5", with ' or h marking hardened levels
+impl FromStr for DerivationPath {
+    type Err = BitcoinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = s.split('/');
+        if segments.next() != Some("m") {
+            return Err(BitcoinError::ParseError(
+                "Derivation path must start with \"m\"".to_string(),
+            ));
+        }
+
+        segments
+            .map(|segment| {
+                let invalid = || {
+                    BitcoinError::ParseError(format!(
+                        "Invalid derivation path segment \"{segment}\""
+                    ))
+                };
+                let (digits, hardened) = match segment.strip_suffix(['\'', 'h', 'H']) {
+                    Some(digits) => (digits, true),
+                    None => (segment, false),
+                };
+                if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+
+                let index: u32 = digits.parse().map_err(|_| invalid())?;
+                if index >= HARDENED_INDEX {
+                    return Err(invalid());
+                }
+                Ok(if hardened {
+                    index | HARDENED_INDEX
+                } else {
+                    index
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(DerivationPath)
+    }
+}
+
+pub fn fingerprint(pubkey_sec: &[u8]) -> Fingerprint {
+    hash160(pubkey_sec)[..4].try_into().expect("4 byte slice")
+}
+
+// Split a PSBT key origin value into the master fingerprint and the path
+pub fn parse_key_origin(value: &[u8]) -> Result<(Fingerprint, DerivationPath), BitcoinError> {
+    let (fingerprint, path) = value.split_first_chunk::<4>().ok_or_else(|| {
+        BitcoinError::ParseError("Key origin is missing the fingerprint".to_string())
+    })?;
+    Ok((*fingerprint, DerivationPath::from_bytes(path)?))
+}
+
+// BIP-32 extended public key
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendedPubKey {
+    pub version: [u8; 4],
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+    pub chain_code: [u8; 32],
+    pub public_key: [u8; 33],
+}
+
+impl ExtendedPubKey {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, BitcoinError> {
+        if data.len() != EXTENDED_KEY_LEN {
+            return Err(BitcoinError::ParseError(format!(
+                "Extended key must be {EXTENDED_KEY_LEN} bytes, got {}",
+                data.len()
+            )));
+        }
+
+        let public_key: [u8; 33] = data[45..78].try_into().expect("33 byte slice");
+        if public_key[0] != 0x02 && public_key[0] != 0x03 {
+            return Err(BitcoinError::ParseError(
+                "Extended public key must hold a compressed public key".to_string(),
+            ));
+        }
+
+        Ok(ExtendedPubKey {
+            version: data[0..4].try_into().expect("4 byte slice"),
+            depth: data[4],
+            parent_fingerprint: data[5..9].try_into().expect("4 byte slice"),
+            child_number: u32::from_be_bytes(data[9..13].try_into().expect("4 byte slice")),
+            chain_code: data[13..45].try_into().expect("32 byte slice"),
+            public_key,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(EXTENDED_KEY_LEN);
+        data.extend(self.version);
+        data.push(self.depth);
+        data.extend(self.parent_fingerprint);
+        data.extend(self.child_number.to_be_bytes());
+        data.extend(self.chain_code);
+        data.extend(self.public_key);
+        data
+    }
+
+    // BIP-32 public child key derivation (non-hardened indices only)
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedPubKey, BitcoinError> {
+        if index >= HARDENED_INDEX {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+
+        let mut data = Vec::with_capacity(37);
+        data.extend(self.public_key);
+        data.extend(index.to_be_bytes());
+        let hmac = hmac_sha512(&self.chain_code, &data);
+
+        let tweak = U256::from_be_bytes(hmac[..32].try_into().expect("32 byte slice"));
+        if tweak >= CURVE_ORDER {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+
+        // Child key = tweak * G + parent key
+        let parent = Point::<U256>::from_sec(&self.public_key)?;
+        let child = Point::generator()
+            .scalar_mul(&tweak)
+            .and_then(|tweak_point| tweak_point.add(&parent))
+            .ok_or(BitcoinError::InvalidTransaction)?;
+
+        let depth = self
+            .depth
+            .checked_add(1)
+            .ok_or(BitcoinError::InvalidTransaction)?;
+
+        Ok(ExtendedPubKey {
+            version: self.version,
+            depth,
+            parent_fingerprint: fingerprint(&self.public_key),
+            child_number: index,
+            chain_code: hmac[32..].try_into().expect("32 byte slice"),
+            public_key: child.to_sec_compressed(),
+        })
+    }
+
+    // Base58Check encoding, e.g. "xpub..."
+    pub fn encode(&self) -> String {
+        base58::encode_check(&self.to_bytes())
+    }
+}
+
+// Parse a Base58Check encoded extended public key
+pub fn decode_xpub(s: &str) -> Result<ExtendedPubKey, BitcoinError> {
+    let payload = base58::decode_check(s)?;
+    ExtendedPubKey::from_bytes(&payload)
+}
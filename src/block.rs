@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use crate::decoder::Decoder;
+use crate::script::ScriptType;
+use crate::{BitcoinError, LegacyTransaction, hashes, read_version, write_version};
+
+// Serialized block header length
+pub const HEADER_SIZE: usize = 80;
+
+// Compact target of the genesis block, which defines difficulty 1
+pub const GENESIS_BITS: u32 = 0x1d00ffff;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockHeader {
+    pub version: i32,
+    pub prev_blockhash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, BitcoinError> {
+        if data.len() != HEADER_SIZE {
+            return Err(BitcoinError::ParseError(format!(
+                "Block header must be {HEADER_SIZE} bytes, got {}",
+                data.len()
+            )));
+        }
+
+        let mut decoder = Decoder::new(data);
+        Ok(BlockHeader {
+            version: read_version(decoder.read_array("version")?),
+            prev_blockhash: decoder.read_array("previous block hash")?,
+            merkle_root: decoder.read_array("merkle root")?,
+            time: decoder.read_u32("time")?,
+            bits: decoder.read_u32("bits")?,
+            nonce: decoder.read_u32("nonce")?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(HEADER_SIZE);
+        write_version(&mut buf, self.version);
+        buf.extend(self.prev_blockhash);
+        buf.extend(self.merkle_root);
+        buf.extend(self.time.to_le_bytes());
+        buf.extend(self.bits.to_le_bytes());
+        buf.extend(self.nonce.to_le_bytes());
+        buf
+    }
+
+    // Double SHA-256 of the header, in internal byte order
+    pub fn block_hash(&self) -> [u8; 32] {
+        hashes::sha256d(&self.to_bytes())
+    }
+
+    // Header time as "YYYY-MM-DD HH:MM:SS UTC"
+    pub fn timestamp_datetime(&self) -> String {
+        let secs = self.time as i64;
+        let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+        let secs_of_day = secs.rem_euclid(86_400);
+        format!(
+            "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+            secs_of_day / 3_600,
+            secs_of_day % 3_600 / 60,
+            secs_of_day % 60
+        )
+    }
+
+    // Difficulty relative to the genesis target (difficulty 1)
+    pub fn difficulty(&self) -> f64 {
+        compact_to_target_f64(GENESIS_BITS) / compact_to_target_f64(self.bits)
+    }
+
+    pub fn nonce(&self) -> u32 {
+        self.nonce
+    }
+}
+
+// A block header with its transactions, coinbase first
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub transactions: Vec<LegacyTransaction>,
+}
+
+impl Block {
+    // Number of outputs of each script type across all transactions
+    pub fn output_type_histogram(&self) -> HashMap<ScriptType, usize> {
+        let mut histogram = HashMap::new();
+        for output in self.transactions.iter().flat_map(|tx| &tx.outputs) {
+            *histogram.entry(output.script_type()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    // Total inputs and total outputs across all transactions
+    pub fn io_totals(&self) -> (usize, usize) {
+        self.transactions
+            .iter()
+            .fold((0, 0), |(inputs, outputs), tx| {
+                (inputs + tx.inputs.len(), outputs + tx.outputs.len())
+            })
+    }
+}
+
+// Difficulty adjustment period: 2016 blocks at ten minutes each
+pub const TARGET_TIMESPAN: u32 = 14 * 24 * 60 * 60;
+
+// Whether `new_bits` is the target that follows `old_bits` after a period that
+// took `actual_timespan` seconds: the old target scaled by actual / expected
+// time, with the timespan clamped to between a quarter and four times the
+// expected one, capped at the proof-of-work limit and re-encoded compactly
+pub fn valid_retarget(old_bits: u32, actual_timespan: u32, new_bits: u32) -> bool {
+    let Some(mut target) = compact_to_target(old_bits) else {
+        return false;
+    };
+    let timespan = actual_timespan.clamp(TARGET_TIMESPAN / 4, TARGET_TIMESPAN * 4);
+    mul_target(&mut target, timespan);
+    div_target(&mut target, TARGET_TIMESPAN);
+
+    let pow_limit = compact_to_target(GENESIS_BITS).expect("valid limit");
+    if target.iter().rev().cmp(pow_limit.iter().rev()).is_gt() {
+        target = pow_limit;
+    }
+    target_to_compact(&target) == new_bits
+}
+
+// 288-bit unsigned target as little-endian 32-bit limbs, leaving headroom above
+// 256 bits for the retarget multiplication
+type Target = [u32; 9];
+
+// Expand compact bits into a full target; `None` when negative or too large
+fn compact_to_target(bits: u32) -> Option<Target> {
+    let exponent = bits >> 24;
+    let mantissa = bits & 0x007f_ffff;
+    if bits & 0x0080_0000 != 0 && mantissa != 0 {
+        return None;
+    }
+
+    let mut target = [0u32; 9];
+    if exponent <= 3 {
+        target[0] = mantissa >> (8 * (3 - exponent));
+        return Some(target);
+    }
+    let shift = 8 * (exponent as usize - 3);
+    if shift + 24 > 256 {
+        return None;
+    }
+    let shifted = (mantissa as u64) << (shift % 32);
+    target[shift / 32] = shifted as u32;
+    target[shift / 32 + 1] = (shifted >> 32) as u32;
+    Some(target)
+}
+
+fn mul_target(target: &mut Target, factor: u32) {
+    let mut carry = 0u64;
+    for limb in target.iter_mut() {
+        let product = *limb as u64 * factor as u64 + carry;
+        *limb = product as u32;
+        carry = product >> 32;
+    }
+}
+
+fn div_target(target: &mut Target, divisor: u32) {
+    let mut remainder = 0u64;
+    for limb in target.iter_mut().rev() {
+        let current = (remainder << 32) | *limb as u64;
+        *limb = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+}
+
+// Compact encoding: byte length in the top byte and the three most significant
+// bytes below it, shifted down a byte when that would set the sign bit
+fn target_to_compact(target: &Target) -> u32 {
+    let Some(top) = target.iter().rposition(|&limb| limb != 0) else {
+        return 0;
+    };
+    let bit_len = top * 32 + (32 - target[top].leading_zeros() as usize);
+    let mut size = bit_len.div_ceil(8);
+
+    let mut mantissa = if size <= 3 {
+        target[0] << (8 * (3 - size))
+    } else {
+        let offset = 8 * (size - 3);
+        let (word, bit) = (offset / 32, offset % 32);
+        let high = if bit > 0 {
+            target.get(word + 1).map_or(0, |&limb| limb << (32 - bit))
+        } else {
+            0
+        };
+        ((target[word] >> bit) | high) & 0x00ff_ffff
+    };
+    if mantissa & 0x0080_0000 != 0 {
+        mantissa >>= 8;
+        size += 1;
+    }
+    mantissa | (size as u32) << 24
+}
+
+// BIP-152 short transaction ID: SipHash-2-4 of the txid, keyed by the first 16
+// bytes of SHA-256(header || nonce), truncated to 6 bytes. The key comes from
+// the serialized header rather than its hash, so the header itself is taken.
+pub fn short_txid(header: &BlockHeader, header_nonce: u64, txid: &[u8; 32]) -> u64 {
+    let mut preimage = header.to_bytes();
+    preimage.extend(header_nonce.to_le_bytes());
+    let key = hashes::sha256(&preimage);
+
+    let k0 = u64::from_le_bytes(key[..8].try_into().expect("8 byte key half"));
+    let k1 = u64::from_le_bytes(key[8..16].try_into().expect("8 byte key half"));
+    hashes::siphash24(k0, k1, txid) & 0xffff_ffff_ffff
+}
+
+// Approximate value of a compact target: mantissa * 256^(exponent - 3)
+fn compact_to_target_f64(bits: u32) -> f64 {
+    let exponent = (bits >> 24) as i32;
+    let mantissa = (bits & 0x007f_ffff) as f64;
+    mantissa * 256f64.powi(exponent - 3)
+}
+
+// Convert days since 1970-01-01 into a (year, month, day) civil date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Consensus limit on block weight (BIP-141)
+pub const MAX_BLOCK_WEIGHT: usize = 4_000_000;
+
+pub const COIN: u64 = 100_000_000;
+// No amount, alone or summed, may exceed the total supply
+pub const MAX_MONEY: u64 = 21_000_000 * COIN;
+pub const SUBSIDY_HALVING_INTERVAL: u32 = 210_000;
+
+// Block reward at `height`: 50 BTC, halving every 210,000 blocks
+pub fn block_subsidy(height: u32) -> u64 {
+    let halvings = height / SUBSIDY_HALVING_INTERVAL;
+    if halvings >= 64 {
+        return 0;
+    }
+    (50 * COIN) >> halvings
+}
+
+// A coinbase may claim at most the block subsidy plus the fees of its transactions
+pub fn validate_coinbase_amount(
+    coinbase: &LegacyTransaction,
+    height: u32,
+    total_fees: u64,
+) -> Result<(), BitcoinError> {
+    let allowed = block_subsidy(height)
+        .checked_add(total_fees)
+        .ok_or(BitcoinError::InvalidAmount)?;
+
+    if coinbase.total_output_value()? > allowed {
+        return Err(BitcoinError::InvalidAmount);
+    }
+    Ok(())
+}
+
+// Fees collected by a block: whatever its coinbase claims beyond the subsidy at
+// `height`. A coinbase paying less than the subsidy is rejected here, although
+// consensus allows miners to forgo part of it.
+pub fn block_total_fees(coinbase_output_total: u64, height: u32) -> Result<u64, BitcoinError> {
+    coinbase_output_total
+        .checked_sub(block_subsidy(height))
+        .ok_or(BitcoinError::InvalidAmount)
+}
+
+// Confirmations of a transaction mined at `confirmed_height` once the chain tip is
+// at `tip_height`; 0 if the height is beyond the tip
+pub fn confirmations(confirmed_height: u32, tip_height: u32) -> u32 {
+    if confirmed_height > tip_height {
+        return 0;
+    }
+    tip_height - confirmed_height + 1
+}
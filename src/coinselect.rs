@@ -0,0 +1,299 @@
+use crate::address::{Network, address_to_script_pubkey};
+use crate::fees::{
+    SEGWIT_OVERHEAD_VBYTES, TX_OVERHEAD_VBYTES, dust_threshold, effective_value,
+    estimated_input_vbytes, fee_for_vbytes, is_segwit_script_type, output_vbytes, template_script,
+};
+use crate::script::{ScriptType, witness_script};
+use crate::{BitcoinError, LegacyTransaction, OutPoint, TxInput, TxOutput};
+
+// Outcome of planning a batched payout without building the transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchEstimate {
+    pub total_sent: u64,
+    pub estimated_fee: u64,
+    pub inputs_used: usize,
+    pub needs_change: bool,
+}
+
+// Plan a payout to every recipient, selecting the largest economical UTXOs first. Change is
+// sized as a fresh output of `change_script_type`; when the leftover would be dust it is
+// given to the miner instead.
+pub fn estimate_batch_payout(
+    recipients: &[(String, u64)],
+    utxos: &[(OutPoint, TxOutput)],
+    fee_rate: f64,
+    change_script_type: ScriptType,
+    network: Network,
+) -> Result<BatchEstimate, BitcoinError> {
+    if recipients.is_empty() {
+        return Err(BitcoinError::InvalidAmount);
+    }
+
+    let mut total_sent: u64 = 0;
+    let mut outputs_vbytes = 0;
+    for (address, amount) in recipients {
+        let script_pubkey = address_to_script_pubkey(address, &network)?;
+        if *amount < dust_threshold(&script_pubkey) {
+            return Err(BitcoinError::InvalidAmount);
+        }
+        total_sent = total_sent
+            .checked_add(*amount)
+            .ok_or(BitcoinError::InvalidAmount)?;
+        outputs_vbytes += output_vbytes(&script_pubkey);
+    }
+
+    // UTXOs that cost more to spend than they are worth are never selected
+    let mut sorted: Vec<&TxOutput> = utxos
+        .iter()
+        .map(|(_, utxo)| utxo)
+        .filter(|utxo| effective_value(utxo.value, utxo.script_type(), fee_rate) > 0)
+        .collect();
+    sorted.sort_by_key(|utxo| std::cmp::Reverse(utxo.value));
+    let change_script = template_script(change_script_type);
+
+    let mut selected_value: u64 = 0;
+    let mut inputs_vbytes = 0;
+    let mut any_segwit = false;
+    for (count, utxo) in sorted.iter().enumerate() {
+        let script_type = utxo.script_type();
+        selected_value = selected_value
+            .checked_add(utxo.value)
+            .ok_or(BitcoinError::InvalidAmount)?;
+        inputs_vbytes += estimated_input_vbytes(script_type);
+        any_segwit |= is_segwit_script_type(script_type);
+
+        let overhead = TX_OVERHEAD_VBYTES
+            + if any_segwit {
+                SEGWIT_OVERHEAD_VBYTES
+            } else {
+                0
+            };
+        let base_vbytes = overhead + inputs_vbytes + outputs_vbytes;
+        let fee_without_change = fee_for_vbytes(base_vbytes, fee_rate);
+        let fee_with_change = fee_for_vbytes(base_vbytes + output_vbytes(&change_script), fee_rate);
+
+        let needed_with_change = total_sent
+            .checked_add(fee_with_change)
+            .and_then(|needed| needed.checked_add(dust_threshold(&change_script)))
+            .ok_or(BitcoinError::InvalidAmount)?;
+        let needed_without_change = total_sent
+            .checked_add(fee_without_change)
+            .ok_or(BitcoinError::InvalidAmount)?;
+
+        if selected_value >= needed_with_change {
+            return Ok(BatchEstimate {
+                total_sent,
+                estimated_fee: fee_with_change,
+                inputs_used: count + 1,
+                needs_change: true,
+            });
+        }
+        if selected_value >= needed_without_change {
+            // Any leftover too small for change goes to the fee
+            return Ok(BatchEstimate {
+                total_sent,
+                estimated_fee: selected_value - total_sent,
+                inputs_used: count + 1,
+                needs_change: false,
+            });
+        }
+    }
+
+    Err(BitcoinError::InvalidAmount)
+}
+
+// Change left over after paying `outputs_total` plus an externally computed `fee`
+pub fn compute_change(
+    inputs_total: u64,
+    outputs_total: u64,
+    fee: u64,
+) -> Result<u64, BitcoinError> {
+    outputs_total
+        .checked_add(fee)
+        .and_then(|spent| inputs_total.checked_sub(spent))
+        .ok_or(BitcoinError::InvalidAmount)
+}
+
+// Upper bound on branch-and-bound search steps, as in Bitcoin Core
+const BNB_TOTAL_TRIES: usize = 100_000;
+
+// Branch-and-bound search for a changeless input set: one whose effective value
+// (value minus the fee to spend it) lands between `target` and `target` plus the
+// cost of creating and later spending a change output. Inputs and change are
+// assumed to be P2WPKH. Among the solutions found the one wasting the least excess
+// is returned; `None` means the caller should fall back to selection with change.
+pub fn select_coins_bnb(
+    utxos: &[(OutPoint, u64)],
+    target: u64,
+    fee_rate: f64,
+) -> Option<Vec<OutPoint>> {
+    let input_fee = fee_for_vbytes(estimated_input_vbytes(ScriptType::P2WPKH), fee_rate);
+    let change_script = witness_script(0, &[0; 20]);
+    let cost_of_change = fee_for_vbytes(output_vbytes(&change_script), fee_rate) + input_fee;
+
+    let mut pool: Vec<(&OutPoint, u64)> = utxos
+        .iter()
+        .filter(|(_, value)| *value > input_fee)
+        .map(|(outpoint, value)| (outpoint, value - input_fee))
+        .collect();
+    pool.sort_by_key(|(_, effective)| std::cmp::Reverse(*effective));
+
+    let mut available: u64 = pool.iter().map(|(_, effective)| effective).sum();
+    if available < target {
+        return None;
+    }
+
+    let mut selected_value: u64 = 0;
+    let mut selection: Vec<bool> = Vec::with_capacity(pool.len());
+    let mut best: Option<(Vec<bool>, u64)> = None;
+
+    for _ in 0..BNB_TOTAL_TRIES {
+        let mut backtrack = false;
+        if selected_value + available < target || selected_value > target + cost_of_change {
+            backtrack = true;
+        } else if selected_value >= target {
+            let waste = selected_value - target;
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_waste)| waste <= *best_waste)
+            {
+                best = Some((selection.clone(), waste));
+            }
+            backtrack = true;
+        }
+
+        if backtrack {
+            // Walk back to the most recently included UTXO and try omitting it
+            while selection.last() == Some(&false) {
+                selection.pop();
+                available += pool[selection.len()].1;
+            }
+            let Some(last) = selection.last_mut() else {
+                break;
+            };
+            *last = false;
+            selected_value -= pool[selection.len() - 1].1;
+        } else {
+            let index = selection.len();
+            available -= pool[index].1;
+            // Including an equal UTXO right after omitting one only repeats a branch
+            let repeats_omitted =
+                index > 0 && !selection[index - 1] && pool[index].1 == pool[index - 1].1;
+            selection.push(!repeats_omitted);
+            if !repeats_omitted {
+                selected_value += pool[index].1;
+            }
+        }
+    }
+
+    let (selection, _) = best?;
+    Some(
+        selection
+            .iter()
+            .zip(&pool)
+            .filter(|(included, _)| **included)
+            .map(|(_, (outpoint, _))| (*outpoint).clone())
+            .collect(),
+    )
+}
+
+// Bitcoin Core's waste metric for a changeless selection of P2WPKH inputs: the
+// extra cost of spending them now rather than at `long_term_fee_rate`, plus the
+// excess over `target` given up to fees. Lower is better; it is negative when
+// consolidating at a cheap fee rate outweighs the excess.
+pub fn selection_waste(
+    selected: &[u64],
+    target: u64,
+    fee_rate: f64,
+    long_term_fee_rate: f64,
+) -> i64 {
+    let input_vbytes = estimated_input_vbytes(ScriptType::P2WPKH);
+    let fee = fee_for_vbytes(input_vbytes, fee_rate) as i64;
+    let long_term_fee = fee_for_vbytes(input_vbytes, long_term_fee_rate) as i64;
+
+    let timing_cost = (fee - long_term_fee) * selected.len() as i64;
+    let effective_total: i64 = selected.iter().map(|&value| value as i64 - fee).sum();
+    timing_cost + effective_total - target as i64
+}
+
+// Split a large payout into batches of at most `max_outputs_per_tx` recipients
+// (at least one each), keeping the original order
+pub fn split_payout(
+    recipients: &[(String, u64)],
+    max_outputs_per_tx: usize,
+) -> Vec<Vec<(String, u64)>> {
+    recipients
+        .chunks(max_outputs_per_tx.max(1))
+        .map(<[_]>::to_vec)
+        .collect()
+}
+
+// Spend `target` to `recipient_spk` paying exactly `absolute_fee`, selecting the
+// largest UTXOs first. The remainder goes to `change_spk`; a remainder too small
+// for a non-dust change output would raise the fee, so it is `InvalidAmount`.
+// Inputs signal replaceability.
+pub fn build_spend_with_fee(
+    utxos: &[(OutPoint, TxOutput)],
+    target: u64,
+    recipient_spk: &[u8],
+    change_spk: &[u8],
+    absolute_fee: u64,
+) -> Result<LegacyTransaction, BitcoinError> {
+    if target < dust_threshold(recipient_spk) {
+        return Err(BitcoinError::InvalidAmount);
+    }
+    let needed = target
+        .checked_add(absolute_fee)
+        .ok_or(BitcoinError::InvalidAmount)?;
+
+    let mut sorted: Vec<&(OutPoint, TxOutput)> = utxos.iter().collect();
+    sorted.sort_by_key(|(_, utxo)| std::cmp::Reverse(utxo.value));
+
+    let mut builder = LegacyTransaction::builder().version(2);
+    let mut selected_value: u64 = 0;
+    for (outpoint, utxo) in sorted {
+        if selected_value >= needed {
+            break;
+        }
+        selected_value = selected_value
+            .checked_add(utxo.value)
+            .ok_or(BitcoinError::InvalidAmount)?;
+        builder = builder.add_input(TxInput {
+            previous_output: outpoint.clone(),
+            script_sig: vec![],
+            sequence: 0xFFFFFFFD,
+        });
+    }
+    if selected_value < needed {
+        return Err(BitcoinError::InvalidAmount);
+    }
+
+    builder = builder.add_output(TxOutput {
+        value: target,
+        script_pubkey: recipient_spk.to_vec(),
+    });
+    let change = selected_value - needed;
+    if change > 0 && change < dust_threshold(change_spk) {
+        return Err(BitcoinError::InvalidAmount);
+    }
+    if change > 0 {
+        builder = builder.add_output(TxOutput {
+            value: change,
+            script_pubkey: change_spk.to_vec(),
+        });
+    }
+    Ok(builder.build())
+}
+
+// Warning flag for a change output that would reuse an address: true when
+// `change_spk` is one of the recipient scripts or a script already used before
+pub fn change_reuses_script(
+    change_spk: &[u8],
+    recipient_spks: &[Vec<u8>],
+    used_spks: &[Vec<u8>],
+) -> bool {
+    recipient_spks
+        .iter()
+        .chain(used_spks)
+        .any(|spk| spk.as_slice() == change_spk)
+}
@@ -0,0 +1,73 @@
+use crate::{BitcoinError, varint};
+
+// Cursor over consensus-encoded bytes that reports the offset of any failure
+pub(crate) struct Decoder<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+
+    pub(crate) fn read_bytes(&mut self, len: usize, what: &str) -> Result<&'a [u8], BitcoinError> {
+        if len > self.remaining() {
+            return Err(BitcoinError::ParseError(format!(
+                "{what} needs {len} bytes but only {} remain at offset {}",
+                self.remaining(),
+                self.offset
+            )));
+        }
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_array<const N: usize>(
+        &mut self,
+        what: &str,
+    ) -> Result<[u8; N], BitcoinError> {
+        Ok(self
+            .read_bytes(N, what)?
+            .try_into()
+            .expect("slice of requested length"))
+    }
+
+    pub(crate) fn read_u32(&mut self, what: &str) -> Result<u32, BitcoinError> {
+        Ok(u32::from_le_bytes(self.read_array(what)?))
+    }
+
+    pub(crate) fn read_u64(&mut self, what: &str) -> Result<u64, BitcoinError> {
+        Ok(u64::from_le_bytes(self.read_array(what)?))
+    }
+
+    pub(crate) fn read_compact_size(&mut self, what: &str) -> Result<u64, BitcoinError> {
+        let (value, len) = varint::read_compact_size(&self.data[self.offset..]).map_err(|e| {
+            BitcoinError::ParseError(format!("{what} at offset {}: {e}", self.offset))
+        })?;
+        self.offset += len;
+        Ok(value)
+    }
+
+    // Read a CompactSize length prefix followed by that many bytes
+    pub(crate) fn read_var_bytes(&mut self, what: &str) -> Result<&'a [u8], BitcoinError> {
+        let len_offset = self.offset;
+        let len = self.read_compact_size(what)?;
+        if len > self.remaining() as u64 {
+            return Err(BitcoinError::ParseError(format!(
+                "{what} length {len} at offset {len_offset} exceeds the {} remaining bytes",
+                self.remaining()
+            )));
+        }
+        self.read_bytes(len as usize, what)
+    }
+}
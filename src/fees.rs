@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+
+use crate::script::{ScriptType, classify_script};
+use crate::script::{p2pkh_script, p2sh_script, push_data, witness_script};
+use crate::{BitcoinError, LegacyTransaction, SegwitTransaction, varint};
+
+// Fee estimation heuristics
+
+// Rough number of transactions mined per block, used by the mempool heuristics
+pub const AVERAGE_TXS_PER_BLOCK: usize = 3_000;
+
+// Estimate the fee rate needed to confirm within `target_blocks`, given how many
+// transactions are ahead of ours in the mempool. Approximate: assumes every block
+// clears `AVERAGE_TXS_PER_BLOCK` transactions and scales the rate linearly with the
+// number of blocks we would otherwise wait.
+pub fn required_feerate_for_target(
+    current_feerate: f64,
+    position_in_mempool: usize,
+    target_blocks: u32,
+) -> f64 {
+    let target_blocks = target_blocks.max(1) as f64;
+    let blocks_needed = (position_in_mempool / AVERAGE_TXS_PER_BLOCK + 1) as f64;
+
+    if blocks_needed <= target_blocks {
+        current_feerate
+    } else {
+        current_feerate * blocks_needed / target_blocks
+    }
+}
+
+// Percentage of `recent_rates` at or below `my_rate`, e.g. 80.0 for the 80th percentile.
+// Returns 0.0 when there is no data to compare against.
+pub fn fee_rate_percentile(my_rate: f64, recent_rates: &[f64]) -> f64 {
+    if recent_rates.is_empty() {
+        return 0.0;
+    }
+
+    let at_or_below = recent_rates.iter().filter(|&&rate| rate <= my_rate).count();
+    at_or_below as f64 * 100.0 / recent_rates.len() as f64
+}
+
+// Illustrative 0-100 score of how readily a transaction propagates; not a model
+// of real relay policy. Fee rate dominates with diminishing returns (10 sat/vB is
+// worth half its 80 points), smaller transactions earn up to 15 points, and
+// non-replaceable ones get 5 more since some nodes treat them as final sooner.
+pub fn propagation_score(fee_rate: f64, is_rbf: bool, size: usize) -> f64 {
+    let fee_rate = fee_rate.max(0.0);
+    let fee_points = 80.0 * fee_rate / (fee_rate + 10.0);
+    let size_points = 15.0 * (1.0 - size.min(100_000) as f64 / 100_000.0);
+    let finality_points = if is_rbf { 0.0 } else { 5.0 };
+
+    (fee_points + size_points + finality_points).clamp(0.0, 100.0)
+}
+
+// Expected blocks to confirm at `fee_rate`, from (minimum fee rate, blocks) buckets
+// of historical confirmation times: the bucket with the highest minimum at or
+// below `fee_rate` applies. Returns `u32::MAX` when no bucket does.
+pub fn estimate_confirmation_blocks(fee_rate: f64, buckets: &[(f64, u32)]) -> u32 {
+    buckets
+        .iter()
+        .filter(|(min_rate, _)| *min_rate <= fee_rate)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(u32::MAX, |(_, blocks)| *blocks)
+}
+
+// Fee rate that clears a full mempool's minimum by `safety_margin` sat/vB, so the
+// transaction is not the next one evicted
+pub fn feerate_to_evict(mempool_min_feerate: f64, safety_margin: f64) -> f64 {
+    mempool_min_feerate + safety_margin.max(0.0)
+}
+
+// Fee rate (sat/vB) used by relay policy to decide what counts as dust
+pub const DUST_RELAY_FEERATE: u64 = 3;
+
+// Weight units per virtual byte (BIP-141)
+pub const WITNESS_SCALE_FACTOR: usize = 4;
+
+// Virtual size of `wu` weight units, rounded up
+pub fn wu_to_vbytes(wu: usize) -> usize {
+    wu.div_ceil(WITNESS_SCALE_FACTOR)
+}
+
+pub fn vbytes_to_wu(vb: usize) -> usize {
+    vb * WITNESS_SCALE_FACTOR
+}
+
+// Fixed transaction overhead: version, counts and lock_time
+pub const TX_OVERHEAD_VBYTES: usize = 10;
+// Extra overhead for the segwit marker and flag (2 WU each, rounded up)
+pub const SEGWIT_OVERHEAD_VBYTES: usize = 1;
+
+// Typical signed input size when spending an output of the given type. P2SH is
+// assumed to wrap P2WPKH and P2WSH a 2-of-3 multisig.
+pub fn estimated_input_vbytes(script_type: ScriptType) -> usize {
+    match script_type {
+        ScriptType::P2WPKH => 68,
+        ScriptType::P2TR => 58,
+        ScriptType::P2SH => 91,
+        ScriptType::P2WSH => 105,
+        ScriptType::P2PK => 114,
+        ScriptType::Multisig => 254,
+        ScriptType::P2PKH | ScriptType::OpReturn | ScriptType::NonStandard => 148,
+    }
+}
+
+// Whether spending this type needs a witness (P2SH assumed to wrap segwit)
+pub fn is_segwit_script_type(script_type: ScriptType) -> bool {
+    matches!(
+        script_type,
+        ScriptType::P2WPKH | ScriptType::P2WSH | ScriptType::P2TR | ScriptType::P2SH
+    )
+}
+
+// Serialized size of an output paying to `script_pubkey`
+pub fn output_vbytes(script_pubkey: &[u8]) -> usize {
+    8 + varint::compact_size_len(script_pubkey.len() as u64) + script_pubkey.len()
+}
+
+// Smallest output value relay policy does not consider dust: the cost of creating
+// and later spending the output at `DUST_RELAY_FEERATE`
+pub fn dust_threshold(script_pubkey: &[u8]) -> u64 {
+    let script_type = classify_script(script_pubkey);
+    if script_type == ScriptType::OpReturn {
+        return 0;
+    }
+
+    // Witness programs are cheaper to spend thanks to the witness discount
+    let spend_vbytes = match script_type {
+        ScriptType::P2WPKH | ScriptType::P2WSH | ScriptType::P2TR => 67,
+        _ => 148,
+    };
+    (output_vbytes(script_pubkey) + spend_vbytes) as u64 * DUST_RELAY_FEERATE
+}
+
+// Zero-hash script of the given type, for size calculations. Types that are not
+// used for change are sized as P2PKH.
+pub(crate) fn template_script(script_type: ScriptType) -> Vec<u8> {
+    match script_type {
+        ScriptType::P2SH => p2sh_script(&[0; 20]),
+        ScriptType::P2WPKH => witness_script(0, &[0; 20]),
+        ScriptType::P2WSH => witness_script(0, &[0; 32]),
+        ScriptType::P2TR => witness_script(1, &[0; 32]),
+        _ => p2pkh_script(&[0; 20]),
+    }
+}
+
+// Smallest change output worth creating: not dust, and worth more than it will
+// cost to spend at `fee_rate`. Smaller leftovers are better given to the fee.
+pub fn min_economical_change(change_script_type: ScriptType, fee_rate: f64) -> u64 {
+    let dust = dust_threshold(&template_script(change_script_type));
+    let spend_fee = fee_for_vbytes(estimated_input_vbytes(change_script_type), fee_rate);
+    dust.max(spend_fee + 1)
+}
+
+// Fee paid now plus what it will later cost to spend the change output. Without
+// change (`change_value` of 0) there is nothing further to pay.
+pub fn total_spend_cost(fee: u64, change_value: u64, change_spend_cost: u64) -> u64 {
+    if change_value == 0 {
+        return fee;
+    }
+    fee + change_spend_cost
+}
+
+// Sats saved by spending `utxo_count` inputs of `input_type` now at
+// `current_feerate` rather than later at `future_feerate`; negative when fees
+// are expected to fall. Only the input cost is compared, since the later spend
+// needs its own transaction overhead either way.
+pub fn consolidation_savings(
+    utxo_count: usize,
+    input_type: ScriptType,
+    current_feerate: f64,
+    future_feerate: f64,
+) -> i64 {
+    let input_vbytes = estimated_input_vbytes(input_type) * utxo_count;
+    fee_for_vbytes(input_vbytes, future_feerate) as i64
+        - fee_for_vbytes(input_vbytes, current_feerate) as i64
+}
+
+// UTXO value minus the fee to spend it at `fee_rate`; negative when the input
+// costs more than it is worth
+pub fn effective_value(utxo_value: u64, input_script_type: ScriptType, fee_rate: f64) -> i64 {
+    let spend_fee = fee_for_vbytes(estimated_input_vbytes(input_script_type), fee_rate);
+    utxo_value as i64 - spend_fee as i64
+}
+
+// Fee in satoshis for `vbytes` at `fee_rate` sat/vB, rounded up
+pub fn fee_for_vbytes(vbytes: usize, fee_rate: f64) -> u64 {
+    (vbytes as f64 * fee_rate).ceil() as u64
+}
+
+// Combined virtual size of a package of related transactions, as used for
+// ancestor and descendant fee rate calculations
+pub fn package_vsize(txs: &[LegacyTransaction]) -> usize {
+    txs.iter().map(LegacyTransaction::vsize).sum()
+}
+
+// Whether `child` spends at least one output of `parent`, the structural
+// requirement for child-pays-for-parent
+pub fn is_cpfp_package(parent: &LegacyTransaction, child: &LegacyTransaction) -> bool {
+    child.spends_from(&HashSet::from([parent.txid()]))
+}
+
+// Fee rate a miner sees for a parent and child mined together: both fees over
+// both vsizes, in sat/vB. `InvalidTransaction` if the child does not spend the
+// parent, since unrelated transactions are not mined as a package.
+pub fn cpfp_package_feerate(
+    parent: &LegacyTransaction,
+    parent_input_values: &[u64],
+    child: &LegacyTransaction,
+    child_input_values: &[u64],
+) -> Result<f64, BitcoinError> {
+    if !is_cpfp_package(parent, child) {
+        return Err(BitcoinError::InvalidTransaction);
+    }
+    let fees = parent
+        .fee(parent_input_values)?
+        .checked_add(child.fee(child_input_values)?)
+        .ok_or(BitcoinError::InvalidAmount)?;
+    let vsize = parent.vsize() + child.vsize();
+    Ok(fees as f64 / vsize as f64)
+}
+
+// Vsize reduction of `tx` over the same spend without segwit, where each witness
+// stack is pushed in the scriptSig instead (e.g. P2WPKH versus P2PKH). Returned
+// as a percentage, e.g. 25.0 rather than 0.25: `encoding_efficiency` times 100.
+pub fn weight_savings_vs_legacy(tx: &SegwitTransaction) -> f64 {
+    let mut legacy = tx.to_legacy();
+    for (input, witness) in legacy.inputs.iter_mut().zip(&tx.witnesses) {
+        for item in witness {
+            push_data(&mut input.script_sig, item);
+        }
+    }
+
+    encoding_efficiency(legacy.vsize(), tx.vsize()) * 100.0
+}
+
+// Fraction of the legacy encoding's vsize saved by the segwit one, from 0 to 1
+// rather than a percentage: 0.25 when 200 vB shrinks to 150 vB; negative if the
+// segwit encoding is larger
+pub fn encoding_efficiency(legacy_vsize: usize, segwit_vsize: usize) -> f64 {
+    if legacy_vsize == 0 {
+        return 0.0;
+    }
+    (legacy_vsize as f64 - segwit_vsize as f64) / legacy_vsize as f64
+}
+
+// Smallest absolute fee increase BIP-125 rule 4 allows for a replacement of
+// `current_vsize` vbytes: its own size at the minimum relay fee rate
+pub fn min_fee_bump_increment(current_vsize: usize, min_relay_feerate: f64) -> u64 {
+    fee_for_vbytes(current_vsize, min_relay_feerate)
+}
+
+// BIP-125 rule 1: only an unconfirmed, RBF-signaling transaction can be replaced
+pub fn can_replace(original_confirmed: bool, original_is_rbf: bool) -> Result<(), BitcoinError> {
+    if original_confirmed || !original_is_rbf {
+        return Err(BitcoinError::InvalidTransaction);
+    }
+    Ok(())
+}
+
+// Fee rate paid by draining a change output down to `dust_limit`, for a
+// transaction of `vsize` vbytes. Any fee the inputs already leave over comes on
+// top. Zero when the change has no room above dust.
+pub fn max_feerate_from_change(change_value: u64, vsize: usize, dust_limit: u64) -> f64 {
+    if vsize == 0 {
+        return 0.0;
+    }
+    change_value.saturating_sub(dust_limit) as f64 / vsize as f64
+}
+
+// Default minimum relay fee rate in sat/vB
+pub const MIN_RELAY_FEERATE: f64 = 1.0;
+
+// Minimum fee for a BIP-125 replacement. Rule 3: at least the original's
+// absolute fee. Rule 4: on top of that, pay `min_relay_feerate` for the
+// replacement's own size. Relay policy also requires a strictly higher fee
+// rate than the original, which only binds when the replacement is larger.
+pub fn bip125_replacement_requirements(
+    original_fee: u64,
+    original_vsize: usize,
+    replacement_vsize: usize,
+    min_relay_feerate: f64,
+) -> u64 {
+    let bandwidth_fee = original_fee + min_fee_bump_increment(replacement_vsize, min_relay_feerate);
+
+    let feerate_fee = if original_vsize == 0 {
+        0
+    } else {
+        (original_fee as u128 * replacement_vsize as u128 / original_vsize as u128) as u64 + 1
+    };
+    bandwidth_fee.max(feerate_fee)
+}
@@ -0,0 +1,55 @@
+use crate::BitcoinError;
+
+pub fn encode(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn decode(s: &str) -> Result<Vec<u8>, BitcoinError> {
+    if !s.len().is_multiple_of(2) {
+        return Err(BitcoinError::ParseError(
+            "Hex string has odd length".to_string(),
+        ));
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| BitcoinError::ParseError(format!("Invalid hex at position {i}")))
+        })
+        .collect()
+}
+
+// Hashes such as txids are displayed in reversed byte order
+pub fn encode_reversed(hash: &[u8; 32]) -> String {
+    hash.iter()
+        .rev()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+// Hexdump with 16 bytes per line, each prefixed by its offset and followed by an
+// ASCII gutter, e.g. "00000000  01 00 00 00 ...  |....|"
+pub fn hex_with_offsets(data: &[u8]) -> String {
+    let mut dump = String::with_capacity(data.len().div_ceil(16) * 78);
+    for (line, chunk) in data.chunks(16).enumerate() {
+        dump.push_str(&format!("{:08x} ", line * 16));
+        for i in 0..16 {
+            match chunk.get(i) {
+                Some(byte) => dump.push_str(&format!(" {byte:02x}")),
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str("  |");
+        dump.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+    }
+    dump
+}
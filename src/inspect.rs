@@ -0,0 +1,168 @@
+// One-call decoding of a raw transaction into a human-oriented report
+
+use std::fmt;
+
+use crate::address::{self, Network};
+use crate::script::{self, ScriptType};
+use crate::{
+    BitcoinError, LegacyTransaction, LockTime, RelativeLockTime, SEGWIT_FLAG, SEGWIT_MARKER,
+    SegwitTransaction, fees, hex,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputSummary {
+    // "txid:vout" with the txid in display byte order
+    pub previous_output: String,
+    pub sequence: u32,
+    pub relative_lock_time: Option<RelativeLockTime>,
+    pub witness_items: usize,
+    // Standardness requires scriptSigs to contain only pushes
+    pub push_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputSummary {
+    pub value: u64,
+    pub script_type: ScriptType,
+    pub address: Option<String>,
+    pub standard: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxReport {
+    // Display byte order, as shown by block explorers
+    pub txid: String,
+    pub version: i32,
+    pub segwit: bool,
+    pub size: usize,
+    pub vsize: usize,
+    pub weight: usize,
+    pub inputs: Vec<InputSummary>,
+    pub outputs: Vec<OutputSummary>,
+    pub rbf: bool,
+    pub lock_time: LockTime,
+}
+
+impl TxReport {
+    // One row per output: txid, vout, value_sats, address, script_type
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("txid,vout,value_sats,address,script_type\n");
+        for (vout, output) in self.outputs.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{vout},{},{},{:?}\n",
+                self.txid,
+                output.value,
+                output.address.as_deref().unwrap_or(""),
+                output.script_type
+            ));
+        }
+        csv
+    }
+}
+
+impl fmt::Display for TxReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "txid: {}", self.txid)?;
+        writeln!(
+            f,
+            "version {}, {} bytes, {} vbytes, {} weight units{}",
+            self.version,
+            self.size,
+            self.vsize,
+            self.weight,
+            if self.segwit { ", segwit" } else { "" }
+        )?;
+        writeln!(f, "lock_time: {}", self.lock_time)?;
+        writeln!(f, "replaceable: {}", if self.rbf { "yes" } else { "no" })?;
+
+        writeln!(f, "Inputs:")?;
+        for (i, input) in self.inputs.iter().enumerate() {
+            write!(
+                f,
+                "  #{i} {} sequence 0x{:08x}",
+                input.previous_output, input.sequence
+            )?;
+            // The alternate form (`{:#}`) also checks scriptSigs, as `decode --strict`
+            if f.alternate() && !input.push_only {
+                write!(f, " [non-standard scriptSig]")?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "Outputs:")?;
+        for (i, output) in self.outputs.iter().enumerate() {
+            write!(f, "  #{i} {} sat {:?}", output.value, output.script_type)?;
+            if let Some(address) = &output.address {
+                write!(f, " {address}")?;
+            }
+            if !output.standard {
+                write!(f, " [non-standard]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Decode a hex transaction, legacy or segwit, and compute its derived metadata
+pub fn inspect(raw_hex: &str, network: Network) -> Result<TxReport, BitcoinError> {
+    inspect_bytes(&hex::decode(raw_hex.trim())?, network)
+}
+
+// Same as `inspect` for an already decoded serialization
+pub fn inspect_bytes(data: &[u8], network: Network) -> Result<TxReport, BitcoinError> {
+    let (tx, witnesses, size, weight) = if data.get(4..6) == Some(&[SEGWIT_MARKER, SEGWIT_FLAG][..])
+    {
+        let segwit = SegwitTransaction::from_bytes(data)?;
+        let (size, weight) = (segwit.total_size(), segwit.weight());
+        (segwit.to_legacy(), Some(segwit.witnesses), size, weight)
+    } else {
+        let tx = LegacyTransaction::from_bytes(data)?;
+        let (size, weight) = (tx.size(), tx.weight());
+        (tx, None, size, weight)
+    };
+
+    let inputs = tx
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| InputSummary {
+            previous_output: format!(
+                "{}:{}",
+                hex::encode_reversed(&input.previous_output.txid),
+                input.previous_output.vout
+            ),
+            sequence: input.sequence,
+            relative_lock_time: input.relative_lock_time(),
+            witness_items: witnesses
+                .as_ref()
+                .and_then(|w| w.get(i))
+                .map_or(0, Vec::len),
+            push_only: script::parse_pushes(&input.script_sig).is_some(),
+        })
+        .collect();
+
+    let outputs = tx
+        .outputs
+        .iter()
+        .map(|output| OutputSummary {
+            value: output.value,
+            script_type: output.script_type(),
+            address: address::script_to_address(&output.script_pubkey, &network),
+            standard: script::is_standard_script(&output.script_pubkey),
+        })
+        .collect();
+
+    Ok(TxReport {
+        txid: hex::encode_reversed(&tx.txid()),
+        version: tx.version,
+        segwit: witnesses.is_some(),
+        size,
+        vsize: fees::wu_to_vbytes(weight),
+        weight,
+        inputs,
+        outputs,
+        rbf: tx.signals_rbf(),
+        lock_time: LockTime::from_consensus(tx.lock_time),
+    })
+}
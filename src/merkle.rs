@@ -0,0 +1,121 @@
+// Transaction merkle trees as committed to by block headers
+
+use crate::{BitcoinError, hashes};
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+    hashes::sha256d(&data)
+}
+
+// Hash one level into the next, pairing the last node with itself when odd
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+// Merkle root of txids in internal byte order; `None` for an empty list
+pub fn merkle_root(txids: &[[u8; 32]]) -> Option<[u8; 32]> {
+    let mut level = txids.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.first().copied()
+}
+
+// Sibling hashes from the leaf at `index` up to the root. The flag is true when
+// the sibling sits on the right, i.e. the running hash is the left input.
+pub fn merkle_proof(
+    txids: &[[u8; 32]],
+    index: usize,
+) -> Result<Vec<([u8; 32], bool)>, BitcoinError> {
+    if index >= txids.len() {
+        return Err(BitcoinError::InvalidTransaction);
+    }
+
+    let mut proof = Vec::new();
+    let mut level = txids.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        let sibling_is_right = index.is_multiple_of(2);
+        let sibling = if sibling_is_right {
+            *level.get(index + 1).unwrap_or(&level[index])
+        } else {
+            level[index - 1]
+        };
+        proof.push((sibling, sibling_is_right));
+
+        level = next_level(&level);
+        index /= 2;
+    }
+    Ok(proof)
+}
+
+pub fn verify_merkle_proof(txid: &[u8; 32], proof: &[([u8; 32], bool)], root: &[u8; 32]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(*txid, |hash, (sibling, sibling_is_right)| {
+            if *sibling_is_right {
+                hash_pair(&hash, sibling)
+            } else {
+                hash_pair(sibling, &hash)
+            }
+        });
+    computed == *root
+}
+
+// Streaming merkle root: keeps one pending subtree root per level, like the
+// carries of a binary counter, so memory is logarithmic in the number of txids
+#[derive(Debug, Clone, Default)]
+pub struct MerkleAccumulator {
+    // `pending[level]` is meaningful when bit `level` of `count` is set
+    pending: Vec<[u8; 32]>,
+    count: u64,
+}
+
+impl MerkleAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, txid: [u8; 32]) {
+        let mut hash = txid;
+        let mut level = 0;
+        while self.count & (1 << level) != 0 {
+            hash = hash_pair(&self.pending[level], &hash);
+            level += 1;
+        }
+
+        if level == self.pending.len() {
+            self.pending.push(hash);
+        } else {
+            self.pending[level] = hash;
+        }
+        self.count += 1;
+    }
+
+    // Root over every txid added so far, matching `merkle_root`; `None` if empty
+    pub fn root(&self) -> Option<[u8; 32]> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let mut count = self.count;
+        let mut level = count.trailing_zeros() as usize;
+        let mut hash = self.pending[level];
+        while count != 1 << level {
+            // An unpaired subtree is hashed with itself, as `next_level` does
+            hash = hash_pair(&hash, &hash);
+            count += 1 << level;
+            level += 1;
+            while count & (1 << level) == 0 {
+                hash = hash_pair(&self.pending[level], &hash);
+                level += 1;
+            }
+        }
+        Some(hash)
+    }
+}
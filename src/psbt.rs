@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+
+use crate::bip32::{DerivationPath, ExtendedPubKey, Fingerprint, parse_key_origin};
+use crate::decoder::Decoder;
+use crate::fees::{
+    SEGWIT_OVERHEAD_VBYTES, TX_OVERHEAD_VBYTES, estimated_input_vbytes, is_segwit_script_type,
+    output_vbytes,
+};
+use crate::hashes::hash160;
+use crate::script::{ScriptType, build_p2pkh_script_sig, classify_script};
+use crate::{
+    BitcoinError, BitcoinSerialize, LegacyTransaction, SegwitTransaction, TxOutput, varint,
+};
+
+// "psbt" followed by 0xff
+pub const PSBT_MAGIC: [u8; 5] = [0x70, 0x73, 0x62, 0x74, 0xff];
+
+// Key types (the first byte of each key)
+pub const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+pub const PSBT_GLOBAL_XPUB: u8 = 0x01;
+pub const PSBT_IN_NON_WITNESS_UTXO: u8 = 0x00;
+pub const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+pub const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+pub const PSBT_IN_BIP32_DERIVATION: u8 = 0x06;
+pub const PSBT_IN_FINAL_SCRIPTSIG: u8 = 0x07;
+pub const PSBT_IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+
+// Raw key-value records of one PSBT map, kept in canonical key order
+pub type PsbtMap = BTreeMap<Vec<u8>, Vec<u8>>;
+
+// (public key, signature) pair from a partial signature record
+pub type PartialSig = (Vec<u8>, Vec<u8>);
+
+// SEC-encoded public key taken from a record key
+pub type PublicKey = Vec<u8>;
+
+// Public key with the master fingerprint and path it was derived along
+pub type KeyDerivation = (PublicKey, Fingerprint, DerivationPath);
+
+// Partially signed bitcoin transaction (BIP-174, version 0)
+#[derive(Debug, Clone)]
+pub struct Psbt {
+    pub unsigned_tx: LegacyTransaction,
+    // Global records other than the unsigned transaction
+    pub global: PsbtMap,
+    pub inputs: Vec<PsbtMap>,
+    pub outputs: Vec<PsbtMap>,
+}
+
+impl Psbt {
+    pub fn from_bytes(data: &[u8]) -> Result<Psbt, BitcoinError> {
+        let mut decoder = Decoder::new(data);
+
+        if decoder.read_bytes(PSBT_MAGIC.len(), "PSBT magic").ok() != Some(&PSBT_MAGIC[..]) {
+            return Err(BitcoinError::ParseError("Invalid PSBT magic".to_string()));
+        }
+
+        let mut global = read_map(&mut decoder)?;
+        let tx_bytes = global
+            .remove(&vec![PSBT_GLOBAL_UNSIGNED_TX])
+            .ok_or_else(|| {
+                BitcoinError::ParseError("PSBT is missing the unsigned transaction".to_string())
+            })?;
+        let unsigned_tx = LegacyTransaction::from_bytes(&tx_bytes)?;
+        if unsigned_tx
+            .inputs
+            .iter()
+            .any(|input| !input.script_sig.is_empty())
+        {
+            return Err(BitcoinError::ParseError(
+                "PSBT unsigned transaction has non-empty scriptSigs".to_string(),
+            ));
+        }
+
+        let inputs = (0..unsigned_tx.inputs.len())
+            .map(|_| read_map(&mut decoder))
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = (0..unsigned_tx.outputs.len())
+            .map(|_| read_map(&mut decoder))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if decoder.remaining() > 0 {
+            return Err(BitcoinError::ParseError(format!(
+                "Unexpected trailing data at offset {}",
+                decoder.offset()
+            )));
+        }
+
+        Ok(Psbt {
+            unsigned_tx,
+            global,
+            inputs,
+            outputs,
+        })
+    }
+
+    // Empty PSBT for `tx`, with any scriptSigs stripped as BIP-174 requires
+    pub fn from_unsigned_tx(tx: &LegacyTransaction) -> Psbt {
+        let mut unsigned_tx = tx.clone();
+        for input in &mut unsigned_tx.inputs {
+            input.script_sig.clear();
+        }
+
+        Psbt {
+            inputs: vec![PsbtMap::new(); unsigned_tx.inputs.len()],
+            outputs: vec![PsbtMap::new(); unsigned_tx.outputs.len()],
+            unsigned_tx,
+            global: PsbtMap::new(),
+        }
+    }
+
+    // Attach the output spent by input `index` as its witness UTXO record
+    pub fn set_witness_utxo(&mut self, index: usize, utxo: &TxOutput) -> Result<(), BitcoinError> {
+        let input = self
+            .inputs
+            .get_mut(index)
+            .ok_or(BitcoinError::InvalidTransaction)?;
+
+        let mut value = Vec::new();
+        utxo.consensus_encode(&mut value);
+        input.insert(vec![PSBT_IN_WITNESS_UTXO], value);
+        Ok(())
+    }
+
+    // Merge records from another PSBT for the same unsigned transaction, e.g. the
+    // partial signatures collected by a different signer. Existing records win.
+    pub fn combine(&mut self, other: &Psbt) -> Result<(), BitcoinError> {
+        if self.unsigned_tx.to_bytes() != other.unsigned_tx.to_bytes() {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+
+        merge_map(&mut self.global, &other.global);
+        for (ours, theirs) in self.inputs.iter_mut().zip(&other.inputs) {
+            merge_map(ours, theirs);
+        }
+        for (ours, theirs) in self.outputs.iter_mut().zip(&other.outputs) {
+            merge_map(ours, theirs);
+        }
+        Ok(())
+    }
+
+    // Extended public keys from the global map with the derivation path from the
+    // master key, for wallets checking that inputs and change belong to them
+    pub fn global_xpubs(&self) -> Result<Vec<(ExtendedPubKey, DerivationPath)>, BitcoinError> {
+        self.global
+            .iter()
+            .filter(|(key, _)| key.first() == Some(&PSBT_GLOBAL_XPUB))
+            .map(|(key, value)| {
+                let xpub = ExtendedPubKey::from_bytes(&key[1..])?;
+                let (_, path) = parse_key_origin(value)?;
+                Ok((xpub, path))
+            })
+            .collect()
+    }
+
+    // Partial signatures collected for input `index`
+    pub fn partial_sigs(&self, index: usize) -> Result<Vec<PartialSig>, BitcoinError> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or(BitcoinError::InvalidTransaction)?;
+
+        Ok(input
+            .iter()
+            .filter(|(key, _)| key.first() == Some(&PSBT_IN_PARTIAL_SIG))
+            .map(|(key, sig)| (key[1..].to_vec(), sig.clone()))
+            .collect())
+    }
+
+    // BIP-32 derivation records for input `index`, one per key that may sign it
+    pub fn input_derivations(&self, index: usize) -> Result<Vec<KeyDerivation>, BitcoinError> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or(BitcoinError::InvalidTransaction)?;
+
+        input
+            .iter()
+            .filter(|(key, _)| key.first() == Some(&PSBT_IN_BIP32_DERIVATION))
+            .map(|(key, value)| {
+                let (fingerprint, path) = parse_key_origin(value)?;
+                Ok((key[1..].to_vec(), fingerprint, path))
+            })
+            .collect()
+    }
+
+    // Build the broadcastable transaction once every input is finalized, either by
+    // a final scriptSig record or by a single partial signature over a P2PKH output.
+    // Inputs that need a witness cannot be expressed as a `LegacyTransaction`.
+    pub fn extract_transaction(&self) -> Result<LegacyTransaction, BitcoinError> {
+        let mut tx = self.unsigned_tx.clone();
+
+        for (index, input) in tx.inputs.iter_mut().enumerate() {
+            let map = self
+                .inputs
+                .get(index)
+                .ok_or(BitcoinError::InvalidTransaction)?;
+            if map
+                .get(&vec![PSBT_IN_FINAL_SCRIPTWITNESS])
+                .is_some_and(|witness| witness != &[0x00])
+            {
+                return Err(BitcoinError::InvalidTransaction);
+            }
+
+            if let Some(script_sig) = map.get(&vec![PSBT_IN_FINAL_SCRIPTSIG]) {
+                input.script_sig = script_sig.clone();
+                continue;
+            }
+
+            let spent = self
+                .spent_output(index)?
+                .ok_or(BitcoinError::InvalidTransaction)?;
+            let sigs = self.partial_sigs(index)?;
+            match sigs.as_slice() {
+                [(pubkey, sig)]
+                    if classify_script(&spent.script_pubkey) == ScriptType::P2PKH
+                        && spent.script_pubkey[3..23] == hash160(pubkey) =>
+                {
+                    input.script_sig = build_p2pkh_script_sig(sig, pubkey);
+                }
+                _ => return Err(BitcoinError::InvalidTransaction),
+            }
+        }
+        Ok(tx)
+    }
+
+    // The full previous transaction for input `index`, if present. BIP-174
+    // allows it in either serialization; a segwit one is returned without its
+    // witnesses, which do not affect the txid or outputs.
+    pub fn non_witness_utxo(
+        &self,
+        index: usize,
+    ) -> Result<Option<LegacyTransaction>, BitcoinError> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or(BitcoinError::InvalidTransaction)?;
+
+        input
+            .get(&vec![PSBT_IN_NON_WITNESS_UTXO])
+            .map(|value| {
+                LegacyTransaction::from_bytes(value).or_else(|error| {
+                    SegwitTransaction::from_bytes(value)
+                        .map(|tx| tx.to_legacy())
+                        .map_err(|_| error)
+                })
+            })
+            .transpose()
+    }
+
+    // The output spent by input `index`, from either UTXO record
+    pub fn spent_output(&self, index: usize) -> Result<Option<TxOutput>, BitcoinError> {
+        if let Some(utxo) = self.witness_utxo(index)? {
+            return Ok(Some(utxo));
+        }
+
+        let Some(prev_tx) = self.non_witness_utxo(index)? else {
+            return Ok(None);
+        };
+        let outpoint = &self
+            .unsigned_tx
+            .inputs
+            .get(index)
+            .ok_or(BitcoinError::InvalidTransaction)?
+            .previous_output;
+        if prev_tx.txid() != outpoint.txid {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+        // The previous transaction is present but has no such output
+        prev_tx
+            .outputs
+            .get(outpoint.vout as usize)
+            .cloned()
+            .map(Some)
+            .ok_or(BitcoinError::InvalidTransaction)
+    }
+
+    // Projected vsize once every input is signed, from the typical signed size
+    // of each spent output type. Inputs without a UTXO record cannot be sized
+    // and give `InvalidTransaction`.
+    pub fn estimated_final_vsize(&self) -> Result<usize, BitcoinError> {
+        let mut inputs_vbytes = 0;
+        let mut any_segwit = false;
+        for index in 0..self.unsigned_tx.inputs.len() {
+            let script_type = self
+                .spent_output(index)?
+                .ok_or(BitcoinError::InvalidTransaction)?
+                .script_type();
+            inputs_vbytes += estimated_input_vbytes(script_type);
+            any_segwit |= is_segwit_script_type(script_type);
+        }
+
+        let outputs_vbytes: usize = self
+            .unsigned_tx
+            .outputs
+            .iter()
+            .map(|output| output_vbytes(&output.script_pubkey))
+            .sum();
+        let segwit_overhead = if any_segwit {
+            SEGWIT_OVERHEAD_VBYTES
+        } else {
+            0
+        };
+        Ok(TX_OVERHEAD_VBYTES + segwit_overhead + inputs_vbytes + outputs_vbytes)
+    }
+
+    // The UTXO spent by input `index`, if the PSBT carries it as a witness UTXO
+    pub fn witness_utxo(&self, index: usize) -> Result<Option<TxOutput>, BitcoinError> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or(BitcoinError::InvalidTransaction)?;
+
+        input
+            .get(&vec![PSBT_IN_WITNESS_UTXO])
+            .map(|value| {
+                let mut decoder = Decoder::new(value);
+                TxOutput::consensus_decode(&mut decoder)
+            })
+            .transpose()
+    }
+}
+
+fn merge_map(ours: &mut PsbtMap, theirs: &PsbtMap) {
+    for (key, value) in theirs {
+        ours.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+}
+
+// Re-emit the PSBT with every map in canonical (sorted) key order
+impl BitcoinSerialize for Psbt {
+    fn serialize(&self) -> Vec<u8> {
+        let mut buf = PSBT_MAGIC.to_vec();
+
+        // 0x00 sorts before any other key, so the unsigned transaction comes first
+        write_record(
+            &mut buf,
+            &[PSBT_GLOBAL_UNSIGNED_TX],
+            &self.unsigned_tx.to_bytes(),
+        );
+        write_map(&mut buf, &self.global);
+
+        for map in self.inputs.iter().chain(&self.outputs) {
+            write_map(&mut buf, map);
+        }
+        buf
+    }
+}
+
+fn write_record(buf: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    varint::write_compact_size(buf, key.len() as u64);
+    buf.extend_from_slice(key);
+    varint::write_compact_size(buf, value.len() as u64);
+    buf.extend_from_slice(value);
+}
+
+// Write the records followed by the 0x00 separator
+fn write_map(buf: &mut Vec<u8>, map: &PsbtMap) {
+    for (key, value) in map {
+        write_record(buf, key, value);
+    }
+    buf.push(0x00);
+}
+
+// Read key-value records up to the 0x00 separator
+fn read_map(decoder: &mut Decoder) -> Result<PsbtMap, BitcoinError> {
+    let mut map = PsbtMap::new();
+    loop {
+        let key_offset = decoder.offset();
+        let key = decoder.read_var_bytes("PSBT key")?;
+        if key.is_empty() {
+            return Ok(map);
+        }
+        let value = decoder.read_var_bytes("PSBT value")?;
+
+        if map.insert(key.to_vec(), value.to_vec()).is_some() {
+            return Err(BitcoinError::ParseError(format!(
+                "Duplicate PSBT key at offset {key_offset}"
+            )));
+        }
+    }
+}
@@ -0,0 +1,299 @@
+// Script opcodes and helpers
+
+use crate::BitcoinError;
+
+pub const OP_0: u8 = 0x00;
+pub const OP_PUSHDATA1: u8 = 0x4c;
+pub const OP_PUSHDATA2: u8 = 0x4d;
+pub const OP_PUSHDATA4: u8 = 0x4e;
+pub const OP_1NEGATE: u8 = 0x4f;
+pub const OP_1: u8 = 0x51;
+pub const OP_16: u8 = 0x60;
+pub const OP_RETURN: u8 = 0x6a;
+pub const OP_DROP: u8 = 0x75;
+pub const OP_DUP: u8 = 0x76;
+pub const OP_EQUAL: u8 = 0x87;
+pub const OP_EQUALVERIFY: u8 = 0x88;
+pub const OP_HASH160: u8 = 0xa9;
+pub const OP_CHECKSIG: u8 = 0xac;
+pub const OP_CHECKMULTISIG: u8 = 0xae;
+pub const OP_CHECKLOCKTIMEVERIFY: u8 = 0xb1;
+
+// Consensus limit on the size of a script that can be executed
+pub const MAX_SCRIPT_SIZE: usize = 10_000;
+
+// Whether executing `opcode` always fails: OP_RETURN, the reserved opcodes, the
+// OP_VERIF pair and anything above OP_NOP10
+pub fn is_fail_opcode(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        OP_RETURN | 0x50 | 0x62 | 0x65 | 0x66 | 0x89 | 0x8a | 0xba..
+    )
+}
+
+// Output script templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScriptType {
+    P2PK,
+    P2PKH,
+    P2SH,
+    P2WPKH,
+    P2WSH,
+    P2TR,
+    Multisig,
+    OpReturn,
+    NonStandard,
+}
+
+pub fn classify_script(script: &[u8]) -> ScriptType {
+    match script {
+        [OP_DUP, OP_HASH160, 20, .., OP_EQUALVERIFY, OP_CHECKSIG] if script.len() == 25 => {
+            ScriptType::P2PKH
+        }
+        [OP_HASH160, 20, .., OP_EQUAL] if script.len() == 23 => ScriptType::P2SH,
+        [OP_0, 20, ..] if script.len() == 22 => ScriptType::P2WPKH,
+        [OP_0, 32, ..] if script.len() == 34 => ScriptType::P2WSH,
+        [OP_1, 32, ..] if script.len() == 34 => ScriptType::P2TR,
+        [33, .., OP_CHECKSIG] if script.len() == 35 => ScriptType::P2PK,
+        [65, .., OP_CHECKSIG] if script.len() == 67 => ScriptType::P2PK,
+        [OP_RETURN, ..] => ScriptType::OpReturn,
+        _ if is_multisig(script) => ScriptType::Multisig,
+        _ => ScriptType::NonStandard,
+    }
+}
+
+// Output standardness under default relay policy. Bare multisig is treated as
+// non-standard, matching `-permitbaremultisig=0`, and OP_RETURN outputs must fit
+// the default data carrier size.
+pub fn is_standard_script(script: &[u8]) -> bool {
+    match classify_script(script) {
+        ScriptType::OpReturn => script.len() <= MAX_OP_RETURN_DATA + 3,
+        ScriptType::Multisig | ScriptType::NonStandard => false,
+        _ => true,
+    }
+}
+
+pub const SIGHASH_ALL: u8 = 0x01;
+pub const SIGHASH_NONE: u8 = 0x02;
+pub const SIGHASH_SINGLE: u8 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u8 = 0x80;
+
+// BIP-66 strict DER encoding followed by a defined sighash type:
+// 0x30 <len> 0x02 <lenR> <R> 0x02 <lenS> <S> <sighash>
+pub fn is_valid_signature_encoding(sig: &[u8]) -> bool {
+    if !(9..=73).contains(&sig.len()) || sig[0] != 0x30 || sig[1] as usize != sig.len() - 3 {
+        return false;
+    }
+
+    let len_r = sig[3] as usize;
+    if 5 + len_r >= sig.len() {
+        return false;
+    }
+    let len_s = sig[5 + len_r] as usize;
+    if len_r + len_s + 7 != sig.len() {
+        return false;
+    }
+
+    // Integers must be positive and minimally encoded: no leading zero byte
+    // unless it is needed to clear the sign bit
+    let valid_integer = |start: usize, len: usize| {
+        sig[start - 2] == 0x02
+            && len > 0
+            && sig[start] & 0x80 == 0
+            && !(len > 1 && sig[start] == 0x00 && sig[start + 1] & 0x80 == 0)
+    };
+    if !valid_integer(4, len_r) || !valid_integer(6 + len_r, len_s) {
+        return false;
+    }
+
+    let sighash = sig[sig.len() - 1] & !SIGHASH_ANYONECANPAY;
+    (SIGHASH_ALL..=SIGHASH_SINGLE).contains(&sighash)
+}
+
+// OP_m <pubkey>... OP_n OP_CHECKMULTISIG
+fn is_multisig(script: &[u8]) -> bool {
+    let [first, .., n_op, OP_CHECKMULTISIG] = script else {
+        return false;
+    };
+    if !(OP_1..=OP_16).contains(first) || !(OP_1..=OP_16).contains(n_op) {
+        return false;
+    }
+    let (m, n) = (first - OP_1 + 1, n_op - OP_1 + 1);
+
+    let Some(keys) = parse_pushes(&script[1..script.len() - 2]) else {
+        return false;
+    };
+    m <= n && keys.len() == n as usize && keys.iter().all(|key| key.len() == 33 || key.len() == 65)
+}
+
+// Decode a push-only script into the pushed items. Returns `None` if the script
+// contains any non-push opcode or a truncated push.
+pub fn parse_pushes(script: &[u8]) -> Option<Vec<Vec<u8>>> {
+    collect_pushes(script, false)
+}
+
+// Data pushed anywhere in `script`, skipping other opcodes. Empty if a push is
+// truncated.
+pub fn pushed_items(script: &[u8]) -> Vec<Vec<u8>> {
+    collect_pushes(script, true).unwrap_or_default()
+}
+
+// Whether `data` has the shape of a compressed or uncompressed SEC public key
+pub fn looks_like_pubkey(data: &[u8]) -> bool {
+    matches!(
+        (data.len(), data.first()),
+        (33, Some(0x02 | 0x03)) | (65, Some(0x04))
+    )
+}
+
+// Locktime required by a script starting `<locktime> OP_CHECKLOCKTIMEVERIFY`.
+// The locktime is a script number of at most 5 bytes; negative values, which
+// always fail the check, give `None`.
+pub fn cltv_locktime(script: &[u8]) -> Option<u32> {
+    let (&opcode, rest) = script.split_first()?;
+    let (value, rest) = match opcode {
+        OP_0 => (0, rest),
+        OP_1..=OP_16 => ((opcode - OP_1 + 1) as i64, rest),
+        0x01..=0x05 => {
+            let len = opcode as usize;
+            (script_number(rest.get(..len)?), &rest[len..])
+        }
+        _ => return None,
+    };
+
+    if rest.first() != Some(&OP_CHECKLOCKTIMEVERIFY) {
+        return None;
+    }
+    u32::try_from(value).ok()
+}
+
+// Little-endian sign-magnitude integer as used by script arithmetic
+fn script_number(bytes: &[u8]) -> i64 {
+    let Some((&last, _)) = bytes.split_last() else {
+        return 0;
+    };
+    let mut value = bytes
+        .iter()
+        .rev()
+        .fold(0i64, |acc, &byte| (acc << 8) | byte as i64);
+    if last & 0x80 != 0 {
+        value &= !(0x80i64 << (8 * (bytes.len() - 1)));
+        value = -value;
+    }
+    value
+}
+
+fn collect_pushes(script: &[u8], skip_opcodes: bool) -> Option<Vec<Vec<u8>>> {
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < script.len() {
+        let opcode = script[i];
+        i += 1;
+
+        let len = match opcode {
+            0x01..=0x4b => opcode as usize,
+            OP_PUSHDATA1 => {
+                let len = *script.get(i)? as usize;
+                i += 1;
+                len
+            }
+            OP_PUSHDATA2 => {
+                let len = u16::from_le_bytes(script.get(i..i + 2)?.try_into().ok()?) as usize;
+                i += 2;
+                len
+            }
+            OP_PUSHDATA4 => {
+                let len = u32::from_le_bytes(script.get(i..i + 4)?.try_into().ok()?) as usize;
+                i += 4;
+                len
+            }
+            OP_0 => {
+                items.push(vec![]);
+                continue;
+            }
+            OP_1NEGATE => {
+                items.push(vec![0x81]);
+                continue;
+            }
+            OP_1..=OP_16 => {
+                items.push(vec![opcode - OP_1 + 1]);
+                continue;
+            }
+            _ if skip_opcodes => continue,
+            _ => return None,
+        };
+
+        items.push(script.get(i..i.checked_add(len)?)?.to_vec());
+        i += len;
+    }
+    Some(items)
+}
+
+// Append `data` with the smallest push opcode that can hold it
+pub fn push_data(script: &mut Vec<u8>, data: &[u8]) {
+    match data.len() {
+        len @ 0..=0x4b => script.push(len as u8),
+        len @ 0x4c..=0xff => {
+            script.push(OP_PUSHDATA1);
+            script.push(len as u8);
+        }
+        len @ 0x100..=0xffff => {
+            script.push(OP_PUSHDATA2);
+            script.extend((len as u16).to_le_bytes());
+        }
+        len => {
+            script.push(OP_PUSHDATA4);
+            script.extend((len as u32).to_le_bytes());
+        }
+    }
+    script.extend_from_slice(data);
+}
+
+// OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG
+pub fn p2pkh_script(pubkey_hash: &[u8; 20]) -> Vec<u8> {
+    let mut script = vec![OP_DUP, OP_HASH160];
+    push_data(&mut script, pubkey_hash);
+    script.extend([OP_EQUALVERIFY, OP_CHECKSIG]);
+    script
+}
+
+// <signature with sighash byte> <public key>, spending a P2PKH output
+pub fn build_p2pkh_script_sig(signature: &[u8], pubkey: &[u8]) -> Vec<u8> {
+    let mut script_sig = Vec::with_capacity(signature.len() + pubkey.len() + 2);
+    push_data(&mut script_sig, signature);
+    push_data(&mut script_sig, pubkey);
+    script_sig
+}
+
+// OP_HASH160 <20 bytes> OP_EQUAL
+pub fn p2sh_script(script_hash: &[u8; 20]) -> Vec<u8> {
+    let mut script = vec![OP_HASH160];
+    push_data(&mut script, script_hash);
+    script.push(OP_EQUAL);
+    script
+}
+
+// <version> <program>, where version 0 is OP_0 and 1-16 are OP_1..OP_16
+pub fn witness_script(witness_version: u8, program: &[u8]) -> Vec<u8> {
+    let mut script = vec![if witness_version == 0 {
+        OP_0
+    } else {
+        OP_1 + witness_version - 1
+    }];
+    push_data(&mut script, program);
+    script
+}
+
+// Largest data payload relayed in a standard OP_RETURN output
+pub const MAX_OP_RETURN_DATA: usize = 80;
+
+// OP_RETURN <data>
+pub fn op_return_script(data: &[u8]) -> Result<Vec<u8>, BitcoinError> {
+    if data.len() > MAX_OP_RETURN_DATA {
+        return Err(BitcoinError::InvalidScript);
+    }
+    let mut script = Vec::with_capacity(data.len() + 3);
+    script.push(OP_RETURN);
+    push_data(&mut script, data);
+    Ok(script)
+}
@@ -0,0 +1,327 @@
+use crate::{BitcoinError, Point};
+
+// 256-bit unsigned integer stored as little-endian 64-bit limbs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct U256(pub [u64; 4]);
+
+// Field prime p = 2^256 - 2^32 - 977
+pub const FIELD_PRIME: U256 = U256([
+    0xfffffffefffffc2f,
+    0xffffffffffffffff,
+    0xffffffffffffffff,
+    0xffffffffffffffff,
+]);
+
+// Order n of the generator point
+pub const CURVE_ORDER: U256 = U256([
+    0xbfd25e8cd0364141,
+    0xbaaedce6af48a03b,
+    0xfffffffffffffffe,
+    0xffffffffffffffff,
+]);
+
+const GENERATOR_X: U256 = U256([
+    0x59f2815b16f81798,
+    0x029bfcdb2dce28d9,
+    0x55a06295ce870b07,
+    0x79be667ef9dcbbac,
+]);
+
+const GENERATOR_Y: U256 = U256([
+    0x9c47d08ffb10d4b8,
+    0xfd17b448a6855419,
+    0x5da4fbfc0e1108a8,
+    0x483ada7726a3c465,
+]);
+
+// 2^256 mod p, used to fold the high half of a product back into range
+const FIELD_FOLD: u128 = 0x1000003d1;
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+
+    pub fn from_u64(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().expect("8 byte slice"));
+        }
+        U256(limbs)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    pub fn bit(&self, index: usize) -> bool {
+        (self.0[index / 64] >> (index % 64)) & 1 == 1
+    }
+
+    // Add with carry out
+    fn overflowing_add(&self, other: &U256) -> (U256, bool) {
+        let mut limbs = [0u64; 4];
+        let mut carry = 0u128;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let sum = self.0[i] as u128 + other.0[i] as u128 + carry;
+            *limb = sum as u64;
+            carry = sum >> 64;
+        }
+        (U256(limbs), carry != 0)
+    }
+
+    // Subtract with borrow out
+    fn overflowing_sub(&self, other: &U256) -> (U256, bool) {
+        let mut limbs = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        (U256(limbs), borrow)
+    }
+
+    pub fn add_mod(&self, other: &U256, modulus: &U256) -> U256 {
+        let (sum, carry) = self.overflowing_add(other);
+        if carry || sum >= *modulus {
+            sum.overflowing_sub(modulus).0
+        } else {
+            sum
+        }
+    }
+
+    pub fn sub_mod(&self, other: &U256, modulus: &U256) -> U256 {
+        let (diff, borrow) = self.overflowing_sub(other);
+        if borrow {
+            diff.overflowing_add(modulus).0
+        } else {
+            diff
+        }
+    }
+
+    // Multiplication modulo the field prime
+    pub fn mul_mod_p(&self, other: &U256) -> U256 {
+        let mut product = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let t = product[i + j] as u128 + self.0[i] as u128 * other.0[j] as u128 + carry;
+                product[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            product[i + 4] = carry as u64;
+        }
+
+        // Fold the high 256 bits: hi * 2^256 == hi * FIELD_FOLD (mod p)
+        let mut folded = [0u64; 5];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let t = product[i] as u128 + product[i + 4] as u128 * FIELD_FOLD + carry;
+            folded[i] = t as u64;
+            carry = t >> 64;
+        }
+        folded[4] = carry as u64;
+
+        // Fold the remaining top limb
+        let mut limbs = [0u64; 4];
+        let mut carry = folded[4] as u128 * FIELD_FOLD;
+        for i in 0..4 {
+            let t = folded[i] as u128 + carry;
+            limbs[i] = t as u64;
+            carry = t >> 64;
+        }
+
+        let mut result = U256(limbs);
+        if carry != 0 {
+            result = result
+                .overflowing_add(&U256([FIELD_FOLD as u64, 0, 0, 0]))
+                .0;
+        }
+        if result >= FIELD_PRIME {
+            result = result.overflowing_sub(&FIELD_PRIME).0;
+        }
+        result
+    }
+
+    pub fn pow_mod_p(&self, exponent: &U256) -> U256 {
+        let mut result = U256::from_u64(1);
+        for i in (0..256).rev() {
+            result = result.mul_mod_p(&result);
+            if exponent.bit(i) {
+                result = result.mul_mod_p(self);
+            }
+        }
+        result
+    }
+
+    // Modular inverse via Fermat's little theorem
+    pub fn inv_mod_p(&self) -> U256 {
+        let exponent = FIELD_PRIME.overflowing_sub(&U256::from_u64(2)).0;
+        self.pow_mod_p(&exponent)
+    }
+
+    // Square root modulo p (p = 3 mod 4), if one exists
+    pub fn sqrt_mod_p(&self) -> Option<U256> {
+        // (p + 1) / 4
+        let exponent = U256([
+            0xffffffffbfffff0c,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+            0x3fffffffffffffff,
+        ]);
+        let root = self.pow_mod_p(&exponent);
+        (root.mul_mod_p(&root) == *self).then_some(root)
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+// Affine points on secp256k1; the point at infinity is represented as `None`
+impl Point<U256> {
+    pub fn generator() -> Self {
+        Point::new(GENERATOR_X, GENERATOR_Y)
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        let lhs = self.y.mul_mod_p(&self.y);
+        let rhs = self
+            .x
+            .mul_mod_p(&self.x)
+            .mul_mod_p(&self.x)
+            .add_mod(&U256::from_u64(7), &FIELD_PRIME);
+        lhs == rhs
+    }
+
+    // Parse a compressed (33 byte) or uncompressed (65 byte) SEC public key
+    pub fn from_sec(bytes: &[u8]) -> Result<Self, BitcoinError> {
+        let point = match (bytes.len(), bytes.first()) {
+            (33, Some(&prefix @ (0x02 | 0x03))) => {
+                let x = U256::from_be_bytes(bytes[1..33].try_into().expect("32 byte slice"));
+                if x >= FIELD_PRIME {
+                    return Err(BitcoinError::ParseError(
+                        "Public key x coordinate out of range".to_string(),
+                    ));
+                }
+                let y_squared = x
+                    .mul_mod_p(&x)
+                    .mul_mod_p(&x)
+                    .add_mod(&U256::from_u64(7), &FIELD_PRIME);
+                let mut y = y_squared.sqrt_mod_p().ok_or_else(|| {
+                    BitcoinError::ParseError("Public key is not on the curve".to_string())
+                })?;
+                if y.bit(0) != (prefix == 0x03) {
+                    y = FIELD_PRIME.sub_mod(&y, &FIELD_PRIME);
+                }
+                Point::new(x, y)
+            }
+            (65, Some(0x04)) => Point::new(
+                U256::from_be_bytes(bytes[1..33].try_into().expect("32 byte slice")),
+                U256::from_be_bytes(bytes[33..65].try_into().expect("32 byte slice")),
+            ),
+            _ => {
+                return Err(BitcoinError::ParseError(
+                    "Invalid SEC public key encoding".to_string(),
+                ));
+            }
+        };
+
+        if point.is_on_curve() {
+            Ok(point)
+        } else {
+            Err(BitcoinError::ParseError(
+                "Public key is not on the curve".to_string(),
+            ))
+        }
+    }
+
+    pub fn to_sec_compressed(&self) -> [u8; 33] {
+        let mut bytes = [0u8; 33];
+        bytes[0] = if self.y.bit(0) { 0x03 } else { 0x02 };
+        bytes[1..].copy_from_slice(&self.x.to_be_bytes());
+        bytes
+    }
+
+    pub fn double(&self) -> Option<Self> {
+        if self.y.is_zero() {
+            return None;
+        }
+
+        // lambda = 3x^2 / 2y
+        let x_squared = self.x.mul_mod_p(&self.x);
+        let numerator = x_squared
+            .add_mod(&x_squared, &FIELD_PRIME)
+            .add_mod(&x_squared, &FIELD_PRIME);
+        let denominator = self.y.add_mod(&self.y, &FIELD_PRIME);
+        let lambda = numerator.mul_mod_p(&denominator.inv_mod_p());
+
+        Some(self.with_slope(&lambda, &self.x))
+    }
+
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        if self.x == other.x {
+            return if self.y == other.y {
+                self.double()
+            } else {
+                None
+            };
+        }
+
+        // lambda = (y2 - y1) / (x2 - x1)
+        let numerator = other.y.sub_mod(&self.y, &FIELD_PRIME);
+        let denominator = other.x.sub_mod(&self.x, &FIELD_PRIME);
+        let lambda = numerator.mul_mod_p(&denominator.inv_mod_p());
+
+        Some(self.with_slope(&lambda, &other.x))
+    }
+
+    // Double-and-add scalar multiplication
+    pub fn scalar_mul(&self, scalar: &U256) -> Option<Self> {
+        let mut result: Option<Self> = None;
+        for i in (0..256).rev() {
+            result = result.and_then(|p| p.double());
+            if scalar.bit(i) {
+                result = match result {
+                    Some(p) => p.add(self),
+                    None => Some(self.clone()),
+                };
+            }
+        }
+        result
+    }
+
+    // Third point on the line through self with the given slope
+    fn with_slope(&self, lambda: &U256, other_x: &U256) -> Self {
+        let x3 = lambda
+            .mul_mod_p(lambda)
+            .sub_mod(&self.x, &FIELD_PRIME)
+            .sub_mod(other_x, &FIELD_PRIME);
+        let y3 = lambda
+            .mul_mod_p(&self.x.sub_mod(&x3, &FIELD_PRIME))
+            .sub_mod(&self.y, &FIELD_PRIME);
+        Point::new(x3, y3)
+    }
+}
@@ -0,0 +1,58 @@
+// Pre-segwit signature hashes (the original OP_CHECKSIG digest)
+
+use crate::script::{SIGHASH_ANYONECANPAY, SIGHASH_NONE, SIGHASH_SINGLE};
+use crate::{LegacyTransaction, TxOutput, hashes};
+
+// Digest signed by input `index` spending an output locked by `script_code`.
+// OP_CODESEPARATOR is not stripped from `script_code`.
+pub fn legacy_sighash(
+    tx: &LegacyTransaction,
+    index: usize,
+    script_code: &[u8],
+    sighash_type: u32,
+) -> [u8; 32] {
+    let base_type = (sighash_type & 0x1f) as u8;
+
+    // SIGHASH_SINGLE without a matching output signs the number one, a quirk
+    // kept by consensus
+    if index >= tx.inputs.len() || (base_type == SIGHASH_SINGLE && index >= tx.outputs.len()) {
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        return one;
+    }
+
+    let mut copy = tx.clone();
+    for (i, input) in copy.inputs.iter_mut().enumerate() {
+        if i == index {
+            input.script_sig = script_code.to_vec();
+        } else {
+            input.script_sig.clear();
+            // Other inputs may be updated freely unless everything is signed
+            if base_type == SIGHASH_NONE || base_type == SIGHASH_SINGLE {
+                input.sequence = 0;
+            }
+        }
+    }
+
+    match base_type {
+        SIGHASH_NONE => copy.outputs.clear(),
+        SIGHASH_SINGLE => {
+            copy.outputs.truncate(index + 1);
+            for output in &mut copy.outputs[..index] {
+                *output = TxOutput {
+                    value: u64::MAX,
+                    script_pubkey: vec![],
+                };
+            }
+        }
+        _ => {}
+    }
+
+    if sighash_type & SIGHASH_ANYONECANPAY as u32 != 0 {
+        copy.inputs = vec![copy.inputs.swap_remove(index)];
+    }
+
+    let mut data = copy.to_bytes();
+    data.extend(sighash_type.to_le_bytes());
+    hashes::sha256d(&data)
+}
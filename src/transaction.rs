@@ -0,0 +1,2154 @@
+use clap::{Parser, Subcommand};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+};
+use thiserror::Error;
+
+use crate::decoder::Decoder;
+use crate::{Network, Psbt, ScriptType, TxReport, inspect_bytes};
+use crate::{address, base64, block, fees, hashes, hex, inspect, script, sighash, varint};
+
+// Custom errors for Bitcoin operations
+#[derive(Error, Debug)]
+pub enum BitcoinError {
+    #[error("Invalid transaction format")]
+    InvalidTransaction,
+    #[error("Invalid script format")]
+    InvalidScript,
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Parse error: {0}")]
+    ParseError(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl BitcoinError {
+    // Prefix a `ParseError` message with `msg`, giving "msg: original" so nested
+    // parsers can build a readable chain. Other variants carry no message and
+    // are returned unchanged.
+    pub fn context(self, msg: &str) -> BitcoinError {
+        match self {
+            BitcoinError::ParseError(message) => {
+                BitcoinError::ParseError(format!("{msg}: {message}"))
+            }
+            other => other,
+        }
+    }
+}
+
+// Generic Point struct for Bitcoin addresses or coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub fn new(x: T, y: T) -> Self {
+        // Implement constructor for Point
+        Self { x, y }
+    }
+}
+
+impl<T: FromStr> FromStr for Point<T> {
+    type Err = BitcoinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|s| s.split_once(','))
+            .ok_or(BitcoinError::ParseError("Error parsing values".to_string()))?;
+
+        let x_fromstr = x
+            .parse::<T>()
+            .map_err(|_| BitcoinError::ParseError("Could not parse x value".to_string()))?;
+        let y_fromstr = y
+            .parse::<T>()
+            .map_err(|_| BitcoinError::ParseError("Could not parse y value".to_string()))?;
+
+        Ok(Point {
+            x: x_fromstr,
+            y: y_fromstr,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutPoint {
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+impl OutPoint {
+    // The outpoint referenced by coinbase inputs
+    pub fn null() -> Self {
+        OutPoint {
+            txid: [0; 32],
+            vout: u32::MAX,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == OutPoint::null()
+    }
+}
+
+// Transaction components
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TxInput {
+    pub previous_output: OutPoint,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TxOutput {
+    pub value: u64, // in satoshis
+    pub script_pubkey: Vec<u8>,
+}
+
+// BIP-68 sequence fields
+pub const SEQUENCE_LOCKTIME_DISABLE_FLAG: u32 = 1 << 31;
+pub const SEQUENCE_LOCKTIME_TYPE_FLAG: u32 = 1 << 22;
+pub const SEQUENCE_LOCKTIME_MASK: u32 = 0x0000_ffff;
+// Time-based relative locks count units of 512 seconds
+pub const SEQUENCE_LOCKTIME_GRANULARITY: u32 = 9;
+
+// Relative timelock encoded in an input's sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeLockTime {
+    Blocks(u16),
+    Seconds(u32),
+}
+
+impl RelativeLockTime {
+    pub fn from_sequence(sequence: u32) -> Option<Self> {
+        if sequence & SEQUENCE_LOCKTIME_DISABLE_FLAG != 0 {
+            return None;
+        }
+
+        let value = sequence & SEQUENCE_LOCKTIME_MASK;
+        if sequence & SEQUENCE_LOCKTIME_TYPE_FLAG != 0 {
+            Some(RelativeLockTime::Seconds(
+                value << SEQUENCE_LOCKTIME_GRANULARITY,
+            ))
+        } else {
+            Some(RelativeLockTime::Blocks(value as u16))
+        }
+    }
+
+    // A lock of zero blocks or seconds constrains nothing
+    pub fn is_zero(&self) -> bool {
+        matches!(
+            self,
+            RelativeLockTime::Blocks(0) | RelativeLockTime::Seconds(0)
+        )
+    }
+}
+
+impl fmt::Display for RelativeLockTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RelativeLockTime::Blocks(blocks) => write!(f, "{blocks} blocks"),
+            RelativeLockTime::Seconds(seconds) => write!(f, "{seconds} seconds"),
+        }
+    }
+}
+
+// Human-readable meaning of an input's sequence number in a transaction of
+// `version`. Relative timelocks are only described from version 2, where
+// BIP-68 enforces them, and only when the lock is not zero.
+pub fn describe_sequence(sequence: u32, version: i32) -> String {
+    match sequence {
+        u32::MAX => "final".to_string(),
+        0xFFFFFFFE => "locktime-enabled, no RBF".to_string(),
+        _ => match RelativeLockTime::from_sequence(sequence) {
+            Some(lock) if version >= 2 && !lock.is_zero() => {
+                format!("relative timelock: {lock}")
+            }
+            _ => "RBF-signaling".to_string(),
+        },
+    }
+}
+
+// lock_time values below this are block heights, the rest are Unix timestamps
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+// Highest version relayed by default; version 3 opts in to TRUC (BIP-431) rules
+pub const MAX_STANDARD_VERSION: i32 = 3;
+
+// Expected seconds between blocks
+pub const TARGET_BLOCK_SPACING: u32 = 600;
+
+// Absolute timelock from a transaction's lock_time field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockTime {
+    Blocks(u32),
+    Timestamp(u32),
+}
+
+impl LockTime {
+    pub fn from_consensus(lock_time: u32) -> Self {
+        if lock_time < LOCKTIME_THRESHOLD {
+            LockTime::Blocks(lock_time)
+        } else {
+            LockTime::Timestamp(lock_time)
+        }
+    }
+}
+
+impl fmt::Display for LockTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LockTime::Blocks(height) => write!(f, "block height {height}"),
+            LockTime::Timestamp(time) => write!(f, "unix time {time}"),
+        }
+    }
+}
+
+impl TxInput {
+    pub fn relative_lock_time(&self) -> Option<RelativeLockTime> {
+        RelativeLockTime::from_sequence(self.sequence)
+    }
+
+    // Weight units of the serialized input; any witness is accounted separately
+    pub fn weight(&self) -> usize {
+        let mut buf = Vec::new();
+        self.consensus_encode(&mut buf);
+        fees::vbytes_to_wu(buf.len())
+    }
+
+    pub(crate) fn consensus_encode(&self, buf: &mut Vec<u8>) {
+        buf.extend(self.previous_output.txid);
+        buf.extend(self.previous_output.vout.to_le_bytes());
+        varint::write_compact_size(buf, self.script_sig.len() as u64);
+        buf.extend_from_slice(&self.script_sig);
+        buf.extend(self.sequence.to_le_bytes());
+    }
+
+    pub(crate) fn consensus_decode(decoder: &mut Decoder) -> Result<Self, BitcoinError> {
+        let txid = decoder.read_array("previous txid")?;
+        let vout = decoder.read_u32("previous vout")?;
+        let script_sig = decoder.read_var_bytes("scriptSig")?.to_vec();
+        let sequence = decoder.read_u32("sequence")?;
+
+        Ok(TxInput {
+            previous_output: OutPoint { txid, vout },
+            script_sig,
+            sequence,
+        })
+    }
+}
+
+impl TxOutput {
+    pub fn script_type(&self) -> ScriptType {
+        script::classify_script(&self.script_pubkey)
+    }
+
+    // Outputs carry no witness data, so every byte weighs 4 units
+    pub fn weight(&self) -> usize {
+        fees::vbytes_to_wu(fees::output_vbytes(&self.script_pubkey))
+    }
+
+    // Whether no script could ever spend this output: it starts with an opcode
+    // that always fails, such as OP_RETURN, or is too large to execute
+    pub fn is_provably_unspendable(&self) -> bool {
+        self.script_pubkey
+            .first()
+            .is_some_and(|&opcode| script::is_fail_opcode(opcode))
+            || self.script_pubkey.len() > script::MAX_SCRIPT_SIZE
+    }
+
+    // Absolute timelock of a scriptPubKey guarded by a leading
+    // `<locktime> OP_CHECKLOCKTIMEVERIFY`, if any
+    pub fn timelock_condition(&self) -> Option<LockTime> {
+        script::cltv_locktime(&self.script_pubkey).map(LockTime::from_consensus)
+    }
+
+    // Whether this output pays to `address` on `network`. The address is
+    // decoded rather than compared as text, so uppercase Bech32 matches too.
+    pub fn pays_to(&self, address: &str, network: Network) -> bool {
+        address::address_to_script_pubkey(address.trim(), &network)
+            .is_ok_and(|script_pubkey| script_pubkey == self.script_pubkey)
+    }
+
+    pub(crate) fn consensus_encode(&self, buf: &mut Vec<u8>) {
+        buf.extend(self.value.to_le_bytes());
+        varint::write_compact_size(buf, self.script_pubkey.len() as u64);
+        buf.extend_from_slice(&self.script_pubkey);
+    }
+
+    pub(crate) fn consensus_decode(decoder: &mut Decoder) -> Result<Self, BitcoinError> {
+        let value = decoder.read_u64("output value")?;
+        let script_pubkey = decoder.read_var_bytes("scriptPubKey")?.to_vec();
+
+        Ok(TxOutput {
+            value,
+            script_pubkey,
+        })
+    }
+}
+
+// Serialized size of a transaction split by section, for telemetry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SizeProfile {
+    // Version, input and output counts, lock_time, and any segwit marker and flag
+    pub overhead: usize,
+    pub inputs: usize,
+    pub outputs: usize,
+    pub witness: usize,
+}
+
+impl SizeProfile {
+    pub fn total(&self) -> usize {
+        self.overhead + self.inputs + self.outputs + self.witness
+    }
+}
+
+// Legacy Bitcoin transaction
+#[derive(Debug, Clone)]
+pub struct LegacyTransaction {
+    pub version: i32,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+    pub lock_time: u32,
+}
+
+// Bytes following the version that mark the BIP-144 serialization
+pub const SEGWIT_MARKER: u8 = 0x00;
+pub const SEGWIT_FLAG: u8 = 0x01;
+
+// Segregated witness transaction (BIP-144), one witness stack per input
+#[derive(Debug, Clone)]
+pub struct SegwitTransaction {
+    pub version: i32,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+    pub witnesses: Vec<Vec<Vec<u8>>>,
+    pub lock_time: u32,
+}
+
+impl SegwitTransaction {
+    // Serialize the witness section: for each input, the item count followed by
+    // each length-prefixed item. Inputs without a witness get an empty stack.
+    pub fn serialize_witnesses(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for i in 0..self.inputs.len() {
+            let witness = self.witnesses.get(i).map(Vec::as_slice).unwrap_or_default();
+            varint::write_compact_size(&mut buf, witness.len() as u64);
+            for item in witness {
+                varint::write_compact_size(&mut buf, item.len() as u64);
+                buf.extend_from_slice(item);
+            }
+        }
+        buf
+    }
+
+    // Serialized witness size of each input: item count, then each item with
+    // its length prefix. An input without a witness takes the single count byte.
+    pub fn witness_sizes(&self) -> Vec<usize> {
+        (0..self.inputs.len())
+            .map(|i| {
+                let witness = self.witnesses.get(i).map(Vec::as_slice).unwrap_or_default();
+                varint::compact_size_len(witness.len() as u64)
+                    + witness
+                        .iter()
+                        .map(|item| varint::compact_size_len(item.len() as u64) + item.len())
+                        .sum::<usize>()
+            })
+            .collect()
+    }
+
+    // The same transaction without its witnesses
+    pub fn to_legacy(&self) -> LegacyTransaction {
+        LegacyTransaction {
+            version: self.version,
+            inputs: self.inputs.clone(),
+            outputs: self.outputs.clone(),
+            lock_time: self.lock_time,
+        }
+    }
+
+    // BIP-144 serialization with marker, flag and witnesses
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_version(&mut buf, self.version);
+        buf.extend([SEGWIT_MARKER, SEGWIT_FLAG]);
+        varint::write_compact_size(&mut buf, self.inputs.len() as u64);
+        for input in &self.inputs {
+            input.consensus_encode(&mut buf);
+        }
+        varint::write_compact_size(&mut buf, self.outputs.len() as u64);
+        for output in &self.outputs {
+            output.consensus_encode(&mut buf);
+        }
+        buf.extend(self.serialize_witnesses());
+        buf.extend(self.lock_time.to_le_bytes());
+        buf
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, BitcoinError> {
+        let mut decoder = Decoder::new(data);
+        let version = read_version(decoder.read_array("version")?);
+
+        if decoder.read_array::<2>("segwit marker")? != [SEGWIT_MARKER, SEGWIT_FLAG] {
+            return Err(BitcoinError::ParseError(
+                "Missing segwit marker and flag at offset 4".to_string(),
+            ));
+        }
+
+        let input_count = decoder.read_compact_size("input count")?;
+        let mut inputs = Vec::with_capacity(capped_capacity(input_count));
+        for _ in 0..input_count {
+            inputs.push(TxInput::consensus_decode(&mut decoder)?);
+        }
+
+        let output_count = decoder.read_compact_size("output count")?;
+        let mut outputs = Vec::with_capacity(capped_capacity(output_count));
+        for _ in 0..output_count {
+            outputs.push(TxOutput::consensus_decode(&mut decoder)?);
+        }
+
+        let mut witnesses = Vec::new();
+        for _ in 0..inputs.len() {
+            let item_count = decoder.read_compact_size("witness item count")?;
+            let mut witness = Vec::new();
+            for _ in 0..item_count {
+                witness.push(decoder.read_var_bytes("witness item")?.to_vec());
+            }
+            witnesses.push(witness);
+        }
+
+        let lock_time = decoder.read_u32("lock_time")?;
+        if decoder.remaining() > 0 {
+            return Err(BitcoinError::ParseError(format!(
+                "Unexpected trailing data at offset {}",
+                decoder.offset()
+            )));
+        }
+
+        Ok(SegwitTransaction {
+            version,
+            inputs,
+            outputs,
+            witnesses,
+            lock_time,
+        })
+    }
+
+    // Size without witness data
+    pub fn base_size(&self) -> usize {
+        self.to_legacy().size()
+    }
+
+    pub fn total_size(&self) -> usize {
+        self.to_bytes().len()
+    }
+
+    // Byte breakdown of the BIP-144 serialization; overhead includes the
+    // marker and flag
+    pub fn size_profile(&self) -> SizeProfile {
+        let legacy = self.to_legacy().size_profile();
+        SizeProfile {
+            overhead: legacy.overhead + 2,
+            witness: self.serialize_witnesses().len(),
+            ..legacy
+        }
+    }
+
+    // BIP-141 weight: non-witness bytes count 4 units, witness bytes 1
+    pub fn weight(&self) -> usize {
+        let base_size = self.base_size();
+        fees::vbytes_to_wu(base_size) + (self.total_size() - base_size)
+    }
+
+    pub fn vsize(&self) -> usize {
+        fees::wu_to_vbytes(self.weight())
+    }
+
+    // Share of the weight taken by witness bytes (marker and flag included),
+    // which count one unit each against four for everything else
+    pub fn witness_weight_fraction(&self) -> f64 {
+        let witness_bytes = self.total_size() - self.base_size();
+        witness_bytes as f64 / self.weight() as f64
+    }
+
+    // The txid commits to the transaction without witnesses
+    pub fn txid(&self) -> [u8; 32] {
+        self.to_legacy().txid()
+    }
+
+    pub fn wtxid(&self) -> [u8; 32] {
+        hashes::sha256d(&self.to_bytes())
+    }
+
+    // BIP-144 hex ready for `sendrawtransaction`, after `check_structure`
+    pub fn to_broadcast_hex(&self) -> Result<String, BitcoinError> {
+        self.to_legacy().check_structure()?;
+        Ok(hex::encode(&self.to_bytes()))
+    }
+}
+
+impl LegacyTransaction {
+    pub fn builder() -> LegacyTransactionBuilder {
+        // Return a new builder for constructing a transaction
+        LegacyTransactionBuilder::default()
+    }
+
+    // Full consensus serialization: version, inputs, outputs and lock_time
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_version(&mut buf, self.version);
+        buf.extend(self.serialize_body());
+        buf.extend(self.lock_time.to_le_bytes());
+        buf
+    }
+
+    // The inputs and outputs with their counts, i.e. the serialization without
+    // the version and lock_time that `BitcoinSerialize::serialize` covers
+    pub fn serialize_body(&self) -> Vec<u8> {
+        serialize_inputs_outputs(&self.inputs, &self.outputs)
+    }
+
+    // Whether any input opts in to replacement (BIP-125)
+    pub fn signals_rbf(&self) -> bool {
+        self.inputs.iter().any(|input| input.sequence < 0xFFFFFFFE)
+    }
+
+    // Serialized size in bytes
+    pub fn size(&self) -> usize {
+        self.to_bytes().len()
+    }
+
+    // Legacy transactions carry no witness data, so every byte weighs 4 units
+    pub fn weight(&self) -> usize {
+        fees::vbytes_to_wu(self.size())
+    }
+
+    pub fn vsize(&self) -> usize {
+        fees::wu_to_vbytes(self.weight())
+    }
+
+    // Byte breakdown of the serialization; legacy transactions have no witness
+    pub fn size_profile(&self) -> SizeProfile {
+        let mut inputs = Vec::new();
+        for input in &self.inputs {
+            input.consensus_encode(&mut inputs);
+        }
+        let mut outputs = Vec::new();
+        for output in &self.outputs {
+            output.consensus_encode(&mut outputs);
+        }
+
+        SizeProfile {
+            overhead: 8
+                + varint::compact_size_len(self.inputs.len() as u64)
+                + varint::compact_size_len(self.outputs.len() as u64),
+            inputs: inputs.len(),
+            outputs: outputs.len(),
+            witness: 0,
+        }
+    }
+
+    // Summed (scriptSig, scriptPubKey) lengths across inputs and outputs
+    pub fn total_script_bytes(&self) -> (usize, usize) {
+        (
+            self.inputs.iter().map(|input| input.script_sig.len()).sum(),
+            self.outputs
+                .iter()
+                .map(|output| output.script_pubkey.len())
+                .sum(),
+        )
+    }
+
+    // Double SHA-256 of the serialization, in internal byte order
+    pub fn txid(&self) -> [u8; 32] {
+        hashes::sha256d(&self.to_bytes())
+    }
+
+    // Same as `txid`, resuming from a midstate over a prefix shared by many
+    // transactions, such as the version and common inputs. Falls back to the
+    // full hash when this transaction does not start with that prefix.
+    pub fn txid_with_prefix_cache(&self, cached_prefix: &hashes::Sha256Midstate) -> [u8; 32] {
+        let bytes = self.to_bytes();
+        match bytes.strip_prefix(cached_prefix.prefix()) {
+            Some(suffix) => hashes::sha256(&cached_prefix.finalize_with(suffix)),
+            None => hashes::sha256d(&bytes),
+        }
+    }
+
+    // scriptPubKeys of the outputs spent by each input, as `signing_requests`
+    // expects them. `prevouts` must hold one output per input.
+    pub fn collect_prevout_scripts(
+        &self,
+        prevouts: &[TxOutput],
+    ) -> Result<Vec<Vec<u8>>, BitcoinError> {
+        if prevouts.len() != self.inputs.len() {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+        Ok(prevouts
+            .iter()
+            .map(|prevout| prevout.script_pubkey.clone())
+            .collect())
+    }
+
+    // Legacy sighash for every input, for handing to an external signer.
+    // `prevout_scripts` holds the scriptPubKey spent by each input.
+    pub fn signing_requests(
+        &self,
+        prevout_scripts: &[Vec<u8>],
+        sighash_type: u32,
+    ) -> Result<Vec<[u8; 32]>, BitcoinError> {
+        if prevout_scripts.len() != self.inputs.len() {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+
+        Ok(prevout_scripts
+            .iter()
+            .enumerate()
+            .map(|(index, script_code)| {
+                sighash::legacy_sighash(self, index, script_code, sighash_type)
+            })
+            .collect())
+    }
+
+    // Fill in P2PKH scriptSigs from externally produced (input index, signature,
+    // public key) tuples. Nothing is changed if any index is out of range.
+    pub fn apply_signatures(
+        &mut self,
+        sigs: &[(usize, Vec<u8>, Vec<u8>)],
+    ) -> Result<(), BitcoinError> {
+        if sigs.iter().any(|(index, _, _)| *index >= self.inputs.len()) {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+
+        for (index, signature, pubkey) in sigs {
+            self.inputs[*index].script_sig = script::build_p2pkh_script_sig(signature, pubkey);
+        }
+        Ok(())
+    }
+
+    // One-line confirmation text in the style of a hardware wallet screen, e.g.
+    // "Send 50,000 sat to bc1q..., fee 1,000 sat, change 48,000 sat". Outputs
+    // paying back to one of the spent scripts count as change.
+    pub fn signing_summary(
+        &self,
+        prevouts: &[TxOutput],
+        network: Network,
+    ) -> Result<String, BitcoinError> {
+        if prevouts.len() != self.inputs.len() {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+        let total_in = prevouts
+            .iter()
+            .try_fold(0u64, |total, prevout| total.checked_add(prevout.value))
+            .ok_or(BitcoinError::InvalidAmount)?;
+        let fee = total_in
+            .checked_sub(self.total_output_value()?)
+            .ok_or(BitcoinError::InvalidAmount)?;
+
+        let mut payments = Vec::new();
+        let mut change = 0;
+        for output in &self.outputs {
+            if prevouts
+                .iter()
+                .any(|prevout| prevout.script_pubkey == output.script_pubkey)
+            {
+                change += output.value;
+                continue;
+            }
+            let destination = address::script_to_address(&output.script_pubkey, &network)
+                .unwrap_or_else(|| format!("{:?} script", output.script_type()));
+            payments.push(format!(
+                "{} to {destination}",
+                format_sats_grouped(output.value)
+            ));
+        }
+
+        let payments = if payments.is_empty() {
+            "nothing".to_string()
+        } else {
+            payments.join(", ")
+        };
+        Ok(format!(
+            "Send {payments}, fee {}, change {}",
+            format_sats_grouped(fee),
+            format_sats_grouped(change)
+        ))
+    }
+
+    // PSBT for handing this transaction to signers, with `prevouts[i]` attached
+    // as the witness UTXO of input `i`. Non-witness UTXO records need the full
+    // previous transactions and are left to the caller.
+    pub fn to_psbt(&self, prevouts: &[TxOutput]) -> Result<Psbt, BitcoinError> {
+        if prevouts.len() != self.inputs.len() {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+
+        let mut psbt = Psbt::from_unsigned_tx(self);
+        for (index, prevout) in prevouts.iter().enumerate() {
+            psbt.set_witness_utxo(index, prevout)?;
+        }
+        Ok(psbt)
+    }
+
+    // Reject input and output counts that could not fit in a block even if every
+    // input and output had an empty script
+    pub fn check_io_limits(&self) -> Result<(), BitcoinError> {
+        // Outpoint, empty scriptSig and sequence; value and empty scriptPubKey
+        const MIN_INPUT_SIZE: usize = 41;
+        const MIN_OUTPUT_SIZE: usize = 9;
+
+        let min_size = 8
+            + varint::compact_size_len(self.inputs.len() as u64)
+            + varint::compact_size_len(self.outputs.len() as u64)
+            + self.inputs.len().saturating_mul(MIN_INPUT_SIZE)
+            + self.outputs.len().saturating_mul(MIN_OUTPUT_SIZE);
+        if min_size.saturating_mul(fees::WITNESS_SCALE_FACTOR) > block::MAX_BLOCK_WEIGHT {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+        Ok(())
+    }
+
+    // Context-free consensus checks, as in Core's CheckTransaction: inputs and
+    // outputs present and within size limits, amounts within the money supply,
+    // no outpoint spent twice, and null outpoints only in a valid coinbase
+    pub fn check_structure(&self) -> Result<(), BitcoinError> {
+        if self.inputs.is_empty() || self.outputs.is_empty() || self.has_duplicate_inputs() {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+        self.check_io_limits()?;
+
+        let total = self.total_output_value()?;
+        if total > block::MAX_MONEY {
+            return Err(BitcoinError::InvalidAmount);
+        }
+
+        let spends_null = self
+            .inputs
+            .iter()
+            .any(|input| input.previous_output.is_null());
+        if spends_null && !self.is_valid_coinbase() {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+        Ok(())
+    }
+
+    // Txid with every scriptSig emptied, which stays the same through signing
+    pub fn unsigned_txid(&self) -> [u8; 32] {
+        let mut unsigned = self.clone();
+        for input in &mut unsigned.inputs {
+            input.script_sig.clear();
+        }
+        unsigned.txid()
+    }
+
+    // Compact JSON with keys in sorted order, for comparing against snapshots.
+    // Hashes are in display byte order and scripts are hex.
+    pub fn canonical_json(&self) -> String {
+        let inputs: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|input| {
+                format!(
+                    "{{\"script_sig\":\"{}\",\"sequence\":{},\"txid\":\"{}\",\"vout\":{}}}",
+                    hex::encode(&input.script_sig),
+                    input.sequence,
+                    hex::encode_reversed(&input.previous_output.txid),
+                    input.previous_output.vout
+                )
+            })
+            .collect();
+        let outputs: Vec<String> = self
+            .outputs
+            .iter()
+            .map(|output| {
+                format!(
+                    "{{\"script_pubkey\":\"{}\",\"value\":{}}}",
+                    hex::encode(&output.script_pubkey),
+                    output.value
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"inputs\":[{}],\"lock_time\":{},\"outputs\":[{}],\"txid\":\"{}\",\"version\":{}}}",
+            inputs.join(","),
+            self.lock_time,
+            outputs.join(","),
+            hex::encode_reversed(&self.txid()),
+            self.version
+        )
+    }
+
+    // Identity by txid rather than by field-by-field comparison
+    pub fn same_as(&self, other: &LegacyTransaction) -> bool {
+        self.txid() == other.txid()
+    }
+
+    // Parse a full consensus-encoded legacy transaction. Any structural problem is
+    // reported as a `ParseError` naming the offending field and its byte offset.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, BitcoinError> {
+        let (tx, consumed) = Self::from_bytes_consumed(data)?;
+        if consumed < data.len() {
+            return Err(BitcoinError::ParseError(format!(
+                "Unexpected trailing data at offset {consumed}"
+            )));
+        }
+        Ok(tx)
+    }
+
+    // Parse one transaction from the front of `data`, returning it with the number
+    // of bytes it occupied so a reader can continue with whatever follows
+    pub fn from_bytes_consumed(data: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        let mut decoder = Decoder::new(data);
+        let tx = Self::consensus_decode(&mut decoder)?;
+        Ok((tx, decoder.offset()))
+    }
+
+    // Parse only the outputs of a legacy transaction. Inputs are stepped over
+    // without copying their scripts; the rest is checked as in `from_bytes`.
+    pub fn parse_outputs_only(data: &[u8]) -> Result<Vec<TxOutput>, BitcoinError> {
+        let mut decoder = Decoder::new(data);
+        decoder.read_bytes(4, "version")?;
+
+        let input_count = decoder.read_compact_size("input count")?;
+        for _ in 0..input_count {
+            decoder.read_bytes(36, "previous output")?;
+            decoder.read_var_bytes("scriptSig")?;
+            decoder.read_bytes(4, "sequence")?;
+        }
+
+        let output_count = decoder.read_compact_size("output count")?;
+        let mut outputs = Vec::with_capacity(capped_capacity(output_count));
+        for _ in 0..output_count {
+            outputs.push(TxOutput::consensus_decode(&mut decoder)?);
+        }
+
+        decoder.read_u32("lock_time")?;
+        if decoder.remaining() > 0 {
+            return Err(BitcoinError::ParseError(format!(
+                "Unexpected trailing data at offset {}",
+                decoder.offset()
+            )));
+        }
+        Ok(outputs)
+    }
+
+    // Hex ready for `sendrawtransaction`, after `check_structure` passes.
+    // Witness data lives in `SegwitTransaction::to_broadcast_hex`.
+    pub fn to_broadcast_hex(&self) -> Result<String, BitcoinError> {
+        self.check_structure()?;
+        Ok(hex::encode(&self.to_bytes()))
+    }
+
+    // Check that `data` holds exactly as many inputs and outputs as its
+    // CompactSize counts declare, followed by the 4-byte lock_time and nothing
+    // else. Any mismatch, including a truncated record, is `InvalidTransaction`.
+    pub fn verify_counts(data: &[u8]) -> Result<(), BitcoinError> {
+        let mismatch = |_| BitcoinError::InvalidTransaction;
+        let mut decoder = Decoder::new(data);
+        decoder.read_bytes(4, "version").map_err(mismatch)?;
+
+        let input_count = decoder.read_compact_size("input count").map_err(mismatch)?;
+        for _ in 0..input_count {
+            TxInput::consensus_decode(&mut decoder).map_err(mismatch)?;
+        }
+        let output_count = decoder
+            .read_compact_size("output count")
+            .map_err(mismatch)?;
+        for _ in 0..output_count {
+            TxOutput::consensus_decode(&mut decoder).map_err(mismatch)?;
+        }
+
+        if decoder.remaining() != 4 {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+        Ok(())
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.to_bytes())
+    }
+
+    pub fn from_base64(s: &str) -> Result<Self, BitcoinError> {
+        Self::from_bytes(&base64::decode(s.trim())?)
+    }
+
+    pub(crate) fn consensus_decode(decoder: &mut Decoder) -> Result<Self, BitcoinError> {
+        let version = read_version(decoder.read_array("version")?);
+
+        let input_count = decoder.read_compact_size("input count")?;
+        let mut inputs = Vec::with_capacity(capped_capacity(input_count));
+        for _ in 0..input_count {
+            inputs.push(TxInput::consensus_decode(decoder)?);
+        }
+
+        let output_count = decoder.read_compact_size("output count")?;
+        let mut outputs = Vec::with_capacity(capped_capacity(output_count));
+        for _ in 0..output_count {
+            outputs.push(TxOutput::consensus_decode(decoder)?);
+        }
+
+        let lock_time = decoder.read_u32("lock_time")?;
+
+        Ok(LegacyTransaction {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+
+    // Graphviz rendering of the transaction: previous outpoints flow into the
+    // transaction node, which flows out to each output and its value
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph transaction {\n    rankdir=LR;\n");
+        dot.push_str(&format!(
+            "    tx [shape=box, label=\"version {}\\nlock_time {}\"];\n",
+            self.version, self.lock_time
+        ));
+
+        for (i, input) in self.inputs.iter().enumerate() {
+            dot.push_str(&format!(
+                "    in{i} [shape=ellipse, label=\"{}:{}\"];\n    in{i} -> tx;\n",
+                hex::encode_reversed(&input.previous_output.txid),
+                input.previous_output.vout
+            ));
+        }
+
+        for (i, output) in self.outputs.iter().enumerate() {
+            dot.push_str(&format!(
+                "    out{i} [shape=ellipse, label=\"#{i}: {} sat\"];\n    tx -> out{i};\n",
+                output.value
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    // Guess which output is change. Two heuristics vote: a single non-round amount
+    // among round payments, and a single output whose script type matches the
+    // (P2PKH-looking) inputs. Returns `None` when neither applies or they disagree.
+    pub fn likely_change_index(&self) -> Option<usize> {
+        if self.outputs.len() < 2 {
+            return None;
+        }
+
+        let non_round: Vec<usize> = (0..self.outputs.len())
+            .filter(|&i| !is_round_amount(self.outputs[i].value))
+            .collect();
+        let by_amount = match non_round.as_slice() {
+            [index] => Some(*index),
+            _ => None,
+        };
+
+        let inputs_look_p2pkh = !self.inputs.is_empty()
+            && self.inputs.iter().all(|input| {
+                matches!(
+                    script::parse_pushes(&input.script_sig).as_deref(),
+                    Some([_sig, pubkey]) if pubkey.len() == 33 || pubkey.len() == 65
+                )
+            });
+        let matching: Vec<usize> = (0..self.outputs.len())
+            .filter(|&i| self.outputs[i].script_type() == ScriptType::P2PKH)
+            .collect();
+        let by_script = match matching.as_slice() {
+            [index] if inputs_look_p2pkh => Some(*index),
+            _ => None,
+        };
+
+        match (by_amount, by_script) {
+            (Some(a), Some(b)) if a != b => None,
+            (Some(index), _) | (None, Some(index)) => Some(index),
+            (None, None) => None,
+        }
+    }
+
+    // Fee paid given the values of the spent outputs, in input order
+    pub fn fee(&self, input_values: &[u64]) -> Result<u64, BitcoinError> {
+        if input_values.len() != self.inputs.len() {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+        let total_in = input_values
+            .iter()
+            .try_fold(0u64, |total, &value| total.checked_add(value))
+            .ok_or(BitcoinError::InvalidAmount)?;
+        total_in
+            .checked_sub(self.total_output_value()?)
+            .ok_or(BitcoinError::InvalidAmount)
+    }
+
+    // Warning when the fee rate exceeds `recommended_rate * factor`. Returns
+    // `None` as well when the fee cannot be computed from `input_values`.
+    pub fn overpayment_warning(
+        &self,
+        input_values: &[u64],
+        recommended_rate: f64,
+        factor: f64,
+    ) -> Option<String> {
+        let fee = self.fee(input_values).ok()?;
+        let rate = fee as f64 / self.vsize() as f64;
+        if rate <= recommended_rate * factor {
+            return None;
+        }
+        Some(format!(
+            "fee rate {rate:.1} sat/vB is {:.1}x the recommended {recommended_rate:.1} sat/vB ({})",
+            rate / recommended_rate,
+            format_sats_grouped(fee)
+        ))
+    }
+
+    // Whether `create_replacement` has something to work with: the transaction
+    // signals RBF and its likely change output holds more than the dust limit
+    pub fn bumpable(&self) -> bool {
+        self.signals_rbf()
+            && self.likely_change_index().is_some_and(|index| {
+                let change = &self.outputs[index];
+                change.value > fees::dust_threshold(&change.script_pubkey)
+            })
+    }
+
+    // Fee-bumped copy paying `new_fee_rate` sat/vB, funded by shrinking the
+    // change output picked by `likely_change_index`. The fee also covers the
+    // BIP-125 minimum at the default relay rate. Sizes are measured with the
+    // current scriptSigs, so the result has to be re-signed before broadcast.
+    pub fn create_replacement(
+        &self,
+        new_fee_rate: f64,
+        input_values: &[u64],
+    ) -> Result<LegacyTransaction, BitcoinError> {
+        fees::can_replace(false, self.signals_rbf())?;
+        let original_fee = self.fee(input_values)?;
+        let change_index = self
+            .likely_change_index()
+            .ok_or(BitcoinError::InvalidTransaction)?;
+
+        let vsize = self.vsize();
+        let new_fee =
+            fees::fee_for_vbytes(vsize, new_fee_rate).max(fees::bip125_replacement_requirements(
+                original_fee,
+                vsize,
+                vsize,
+                fees::MIN_RELAY_FEERATE,
+            ));
+
+        let mut replacement = self.clone();
+        let change = &mut replacement.outputs[change_index];
+        change.value = change
+            .value
+            .checked_sub(new_fee - original_fee.min(new_fee))
+            .filter(|&value| value >= fees::dust_threshold(&change.script_pubkey))
+            .ok_or(BitcoinError::InvalidAmount)?;
+        Ok(replacement)
+    }
+
+    // Running total of output values in output order, saturating at u64::MAX
+    pub fn cumulative_output_values(&self) -> Vec<u64> {
+        self.outputs
+            .iter()
+            .scan(0u64, |total, output| {
+                *total = total.saturating_add(output.value);
+                Some(*total)
+            })
+            .collect()
+    }
+
+    // Sum of all output values, failing on overflow
+    pub fn total_output_value(&self) -> Result<u64, BitcoinError> {
+        self.outputs
+            .iter()
+            .try_fold(0u64, |total, output| total.checked_add(output.value))
+            .ok_or(BitcoinError::InvalidAmount)
+    }
+
+    // Whether this transaction could be mined in a block at `height` with median
+    // time past `mtp`, given how many confirmations each spent output has.
+    // Time-based relative locks are approximated at `TARGET_BLOCK_SPACING`
+    // seconds per confirmation since the confirmation times are not known.
+    pub fn spendable_at(&self, height: u32, mtp: u32, input_confirmations: &[u32]) -> bool {
+        let all_final = self.inputs.iter().all(|input| input.sequence == u32::MAX);
+        let absolute_ok = self.lock_time == 0
+            || all_final
+            || match LockTime::from_consensus(self.lock_time) {
+                LockTime::Blocks(lock) => lock < height,
+                LockTime::Timestamp(lock) => lock < mtp,
+            };
+        if !absolute_ok {
+            return false;
+        }
+
+        // BIP-68 only applies from version 2
+        if self.version < 2 {
+            return true;
+        }
+        self.inputs.iter().enumerate().all(|(i, input)| {
+            let confirmations = input_confirmations.get(i).copied().unwrap_or(0);
+            match input.relative_lock_time() {
+                None => true,
+                Some(RelativeLockTime::Blocks(blocks)) => confirmations >= blocks as u32,
+                Some(RelativeLockTime::Seconds(seconds)) => {
+                    confirmations as u64 * TARGET_BLOCK_SPACING as u64 >= seconds as u64
+                }
+            }
+        })
+    }
+
+    // Consensus shape of a coinbase: a single input spending the null outpoint with
+    // a 2 to 100 byte scriptSig, and at least one output
+    pub fn is_valid_coinbase(&self) -> bool {
+        match self.inputs.as_slice() {
+            [input] => {
+                input.previous_output.is_null()
+                    && (2..=100).contains(&input.script_sig.len())
+                    && !self.outputs.is_empty()
+            }
+            _ => false,
+        }
+    }
+
+    // Many inputs swept into a single output
+    pub fn is_consolidation(&self) -> bool {
+        self.is_consolidation_with_threshold(CONSOLIDATION_MIN_INPUTS)
+    }
+
+    pub fn is_consolidation_with_threshold(&self, min_inputs: usize) -> bool {
+        self.outputs.len() == 1 && self.inputs.len() >= min_inputs
+    }
+
+    // Many participants each paying into one of several equal-value outputs
+    pub fn looks_like_coinjoin(&self) -> bool {
+        self.looks_like_coinjoin_with_threshold(COINJOIN_MIN_EQUAL_OUTPUTS)
+    }
+
+    // At least `min_equal_outputs` outputs share a value, with at least one input
+    // per such output
+    pub fn looks_like_coinjoin_with_threshold(&self, min_equal_outputs: usize) -> bool {
+        let mut counts: HashMap<u64, usize> = HashMap::new();
+        for output in &self.outputs {
+            *counts.entry(output.value).or_default() += 1;
+        }
+        let most_equal = counts.values().copied().max().unwrap_or(0);
+
+        most_equal >= min_equal_outputs.max(2) && self.inputs.len() >= most_equal
+    }
+
+    // Number of ways to read the transaction as independent sub-transactions,
+    // Boltzmann-style: partitions of the inputs and outputs into matched groups
+    // where each group's inputs cover its outputs and the leftovers add up to the
+    // fee. 1 means every input could have paid every output together; a 2-in
+    // 2-out mix of equal amounts gives 3. The transaction does not carry the
+    // spent amounts, so `input_values` supplies them. The search is exponential,
+    // so at most `MAX_SUB_TRANSACTION_SIDE` inputs and outputs each are accepted.
+    pub fn sub_transaction_count(&self, input_values: &[u64]) -> Result<usize, BitcoinError> {
+        if input_values.len() != self.inputs.len()
+            || self.inputs.len() > MAX_SUB_TRANSACTION_SIDE
+            || self.outputs.len() > MAX_SUB_TRANSACTION_SIDE
+        {
+            return Err(BitcoinError::InvalidTransaction);
+        }
+
+        let output_values: Vec<u64> = self.outputs.iter().map(|output| output.value).collect();
+        let total_in = input_values
+            .iter()
+            .try_fold(0u64, |total, &value| total.checked_add(value))
+            .ok_or(BitcoinError::InvalidAmount)?;
+        let total_out = self.total_output_value()?;
+        if self.inputs.is_empty() || self.outputs.is_empty() || total_out > total_in {
+            return Err(BitcoinError::InvalidAmount);
+        }
+
+        let in_sums = subset_sums(input_values)?;
+        let out_sums = subset_sums(&output_values)?;
+        let all_inputs = (1u32 << input_values.len()) - 1;
+        let all_outputs = (1u32 << output_values.len()) - 1;
+        Ok(count_sub_transactions(
+            all_inputs,
+            all_outputs,
+            total_in - total_out,
+            &in_sums,
+            &out_sums,
+        ))
+    }
+
+    // BIP-69 ordering as permutations: position `k` of each list holds the
+    // current index of the input (or output) that belongs at position `k`.
+    // Inputs sort by previous txid in displayed byte order, then vout; outputs
+    // by value, then scriptPubKey. Identity maps mean the order already complies.
+    pub fn bip69_reorder_map(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut input_order: Vec<usize> = (0..self.inputs.len()).collect();
+        input_order.sort_by_key(|&i| {
+            let outpoint = &self.inputs[i].previous_output;
+            let mut txid = outpoint.txid;
+            txid.reverse();
+            (txid, outpoint.vout)
+        });
+
+        let mut output_order: Vec<usize> = (0..self.outputs.len()).collect();
+        output_order.sort_by(|&a, &b| {
+            let (a, b) = (&self.outputs[a], &self.outputs[b]);
+            (a.value, &a.script_pubkey).cmp(&(b.value, &b.script_pubkey))
+        });
+        (input_order, output_order)
+    }
+
+    // Share of outputs with round values per `is_round_amount`, which whole-BTC
+    // amounts also satisfy; 0.0 without outputs
+    pub fn round_output_ratio(&self) -> f64 {
+        if self.outputs.is_empty() {
+            return 0.0;
+        }
+        let round = self
+            .outputs
+            .iter()
+            .filter(|output| is_round_amount(output.value))
+            .count();
+        round as f64 / self.outputs.len() as f64
+    }
+
+    // (value, count) for each distinct output value, smallest value first, so
+    // the equal outputs of a CoinJoin collapse into a single entry
+    pub fn output_value_histogram(&self) -> Vec<(u64, usize)> {
+        let mut counts = BTreeMap::new();
+        for output in &self.outputs {
+            *counts.entry(output.value).or_insert(0) += 1;
+        }
+        counts.into_iter().collect()
+    }
+
+    // Version mistakes that still parse: relative timelocks in a version 1
+    // transaction, where BIP-68 does not apply, and versions that are not
+    // relayed by default
+    pub fn version_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.version < 2 {
+            for (index, input) in self.inputs.iter().enumerate() {
+                if let Some(lock) = input.relative_lock_time() {
+                    warnings.push(format!(
+                        "input {index} sets relative timelock {lock}, which version {} \
+                         transactions do not enforce",
+                        self.version
+                    ));
+                }
+            }
+        }
+        if !(1..=MAX_STANDARD_VERSION).contains(&self.version) {
+            warnings.push(format!(
+                "version {} is non-standard; use 1 to {MAX_STANDARD_VERSION}",
+                self.version
+            ));
+        }
+        warnings
+    }
+
+    // Payjoin hint: more outputs than the `expected` payment and change, as when
+    // the receiver adds an output of their own
+    pub fn unusual_output_count(&self, expected: usize) -> bool {
+        self.outputs.len() > expected
+    }
+
+    // Shape of a BIP-127 proof of reserves: a first "challenge" input spending
+    // output 0 of a made-up txid that commits to the proof's message, which
+    // keeps the transaction from ever being valid, followed by the actual
+    // reserve inputs, and a single output
+    pub fn is_proof_of_reserves(&self) -> bool {
+        let Some((challenge, reserves)) = self.inputs.split_first() else {
+            return false;
+        };
+        !reserves.is_empty()
+            && self.outputs.len() == 1
+            && challenge.previous_output.vout == 0
+            && !challenge.previous_output.is_null()
+            && reserves
+                .iter()
+                .all(|input| input.previous_output.txid != challenge.previous_output.txid)
+    }
+
+    // Whether this is a proof of reserves whose challenge commits to `message`
+    pub fn proves_reserves_for(&self, message: &str) -> bool {
+        self.is_proof_of_reserves()
+            && self.inputs[0].previous_output.txid == proof_of_reserves_commitment(message)
+    }
+
+    // Heuristic match for a Lightning commitment transaction (BOLT 3): version
+    // 2, one input spending the funding output, the obscured commitment number
+    // split across a sequence starting 0x80 and a lock_time starting 0x20, and
+    // at least the to_local and to_remote outputs. Ordinary transactions rarely
+    // pick those markers, but nothing here proves a channel exists.
+    pub fn looks_like_ln_commitment(&self) -> bool {
+        self.version == 2
+            && matches!(self.inputs.as_slice(), [input] if input.sequence >> 24 == 0x80)
+            && self.lock_time >> 24 == 0x20
+            && self.outputs.len() >= 2
+    }
+
+    // Indices of Lightning anchor outputs: 330 sat P2WSH outputs, as added to
+    // commitment transactions of anchor channels so either side can CPFP them
+    pub fn anchor_outputs(&self) -> Vec<usize> {
+        (0..self.outputs.len())
+            .filter(|&i| {
+                self.outputs[i].value == LN_ANCHOR_VALUE
+                    && self.outputs[i].script_type() == ScriptType::P2WSH
+            })
+            .collect()
+    }
+
+    // Check whether any outpoint is spent more than once. `from_bytes` does not
+    // call this, so a decoded transaction with duplicates parses; run it (or
+    // `check_structure`) as a separate step after parsing.
+    pub fn has_duplicate_inputs(&self) -> bool {
+        let mut seen = HashSet::with_capacity(self.inputs.len());
+        !self
+            .inputs
+            .iter()
+            .all(|input| seen.insert(&input.previous_output))
+    }
+
+    // Whether any input spends an output of one of the given unconfirmed
+    // transactions, making this a child in a CPFP package
+    pub fn spends_from(&self, unconfirmed_txids: &HashSet<[u8; 32]>) -> bool {
+        self.inputs
+            .iter()
+            .any(|input| unconfirmed_txids.contains(&input.previous_output.txid))
+    }
+
+    // Indices of outputs worth at most `dust_limit`, as sent in dusting attacks
+    pub fn dust_outputs(&self, dust_limit: u64) -> Vec<usize> {
+        self.outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.value <= dust_limit)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    // SEC-encoded public keys pushed by any scriptSig or output script, in order
+    // of first appearance
+    pub fn referenced_pubkeys(&self) -> Vec<Vec<u8>> {
+        let scripts = self
+            .inputs
+            .iter()
+            .map(|input| &input.script_sig)
+            .chain(self.outputs.iter().map(|output| &output.script_pubkey));
+
+        let mut pubkeys: Vec<Vec<u8>> = Vec::new();
+        for item in scripts.flat_map(|script| script::pushed_items(script)) {
+            if script::looks_like_pubkey(&item) && !pubkeys.contains(&item) {
+                pubkeys.push(item);
+            }
+        }
+        pubkeys
+    }
+
+    // Distinct script types among the outputs
+    pub fn script_type_set(&self) -> HashSet<ScriptType> {
+        self.outputs.iter().map(TxOutput::script_type).collect()
+    }
+
+    // Addresses paid by more than one output, in order of first appearance
+    pub fn reused_addresses(&self, network: Network) -> Vec<String> {
+        let mut seen = HashSet::with_capacity(self.outputs.len());
+        let mut reused = Vec::new();
+        for output in &self.outputs {
+            let Some(address) = address::script_to_address(&output.script_pubkey, &network) else {
+                continue;
+            };
+            if !seen.insert(address.clone()) && !reused.contains(&address) {
+                reused.push(address);
+            }
+        }
+        reused
+    }
+}
+
+impl fmt::Display for LegacyTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Transaction (version {}, lock_time {})",
+            self.version, self.lock_time
+        )?;
+
+        writeln!(f, "Inputs:")?;
+        for (i, input) in self.inputs.iter().enumerate() {
+            write!(
+                f,
+                "  #{i} {}:{} sequence 0x{:08x}",
+                hex::encode_reversed(&input.previous_output.txid),
+                input.previous_output.vout,
+                input.sequence
+            )?;
+            // BIP-68 only applies from version 2; `version_warnings` flags the rest
+            let lock = input
+                .relative_lock_time()
+                .filter(|lock| self.version >= 2 && !lock.is_zero());
+            if let Some(lock) = lock {
+                write!(f, " relative locktime: {lock}")?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "Outputs:")?;
+        for (i, output) in self.outputs.iter().enumerate() {
+            writeln!(f, "  #{i} {} sat", output.value)?;
+        }
+        Ok(())
+    }
+}
+
+// Txid used by the challenge input of a BIP-127 proof of reserves
+pub fn proof_of_reserves_commitment(message: &str) -> [u8; 32] {
+    hashes::sha256(format!("Proof-of-Reserves: {message}").as_bytes())
+}
+
+// Run `check_structure` on every transaction, spreading the batch over the
+// available cores. Results are in the same order as `txs`.
+pub fn validate_batch(txs: &[LegacyTransaction]) -> Vec<Result<(), BitcoinError>> {
+    let workers = thread::available_parallelism().map_or(1, usize::from);
+    let chunk_size = txs.len().div_ceil(workers).max(1);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = txs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(|| {
+                    chunk
+                        .iter()
+                        .map(LegacyTransaction::check_structure)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("validation worker panicked"))
+            .collect()
+    })
+}
+
+// Every structural and policy problem with `tx` instead of just the first, each
+// as a `ParseError` naming the problem and the input or output involved, e.g.
+// "output 2: value 100 below dust threshold 546". Covers missing inputs or
+// outputs, duplicated outpoints, counts beyond block limits, an overflowing or
+// excessive total, dust outputs and non-standard output scripts. An empty
+// vector means no problems were found.
+pub fn validate_all(tx: &LegacyTransaction) -> Vec<BitcoinError> {
+    let mut problems = Vec::new();
+    if tx.inputs.is_empty() {
+        problems.push("no inputs".to_string());
+    }
+    if tx.outputs.is_empty() {
+        problems.push("no outputs".to_string());
+    }
+
+    let mut first_spender = HashMap::new();
+    for (index, input) in tx.inputs.iter().enumerate() {
+        if let Some(first) = first_spender.insert(&input.previous_output, index) {
+            problems.push(format!(
+                "input {index}: spends the same outpoint as input {first}"
+            ));
+        }
+    }
+    if tx.check_io_limits().is_err() {
+        problems.push(format!(
+            "{} inputs and {} outputs cannot fit in a block",
+            tx.inputs.len(),
+            tx.outputs.len()
+        ));
+    }
+    match tx.total_output_value() {
+        Ok(total) if total > block::MAX_MONEY => {
+            problems.push(format!("output total {total} exceeds the money supply"))
+        }
+        Ok(_) => {}
+        Err(_) => problems.push("output total overflows".to_string()),
+    }
+
+    for (index, output) in tx.outputs.iter().enumerate() {
+        let dust = fees::dust_threshold(&output.script_pubkey);
+        if !output.is_provably_unspendable() && output.value < dust {
+            problems.push(format!(
+                "output {index}: value {} below dust threshold {dust}",
+                output.value
+            ));
+        }
+        if !script::is_standard_script(&output.script_pubkey) {
+            problems.push(format!("output {index}: non-standard script"));
+        }
+    }
+    problems.into_iter().map(BitcoinError::ParseError).collect()
+}
+
+// Structural check that `input` and its `witness` have the shape needed to
+// spend `prevout`, without verifying signatures. `TxInput` carries no witness,
+// so it is passed separately; an empty slice means none. P2SH may wrap segwit,
+// so any witness is accepted there. Non-standard prevouts are not checked.
+pub fn validate_input_script(
+    input: &TxInput,
+    witness: &[Vec<u8>],
+    prevout: &TxOutput,
+) -> Result<(), BitcoinError> {
+    let pushes = script::parse_pushes(&input.script_sig);
+    let valid = match prevout.script_type() {
+        ScriptType::P2PKH => {
+            witness.is_empty()
+                && pushes.is_some_and(|items| {
+                    items.len() == 2 && !items[0].is_empty() && script::looks_like_pubkey(&items[1])
+                })
+        }
+        ScriptType::P2PK => witness.is_empty() && pushes.is_some_and(|items| items.len() == 1),
+        // The extra leading item is the dummy consumed by OP_CHECKMULTISIG
+        ScriptType::Multisig => {
+            witness.is_empty()
+                && pushes.is_some_and(|items| items.len() >= 2 && items[0].is_empty())
+        }
+        ScriptType::P2SH => pushes.is_some_and(|items| !items.is_empty()),
+        ScriptType::P2WPKH => {
+            input.script_sig.is_empty()
+                && witness.len() == 2
+                && !witness[0].is_empty()
+                && witness[1].len() == 33
+                && script::looks_like_pubkey(&witness[1])
+        }
+        // The last item is the witness script, which must hash to the program
+        ScriptType::P2WSH => {
+            input.script_sig.is_empty()
+                && witness
+                    .last()
+                    .is_some_and(|script| hashes::sha256(script) == prevout.script_pubkey[2..])
+        }
+        ScriptType::P2TR => input.script_sig.is_empty() && !witness.is_empty(),
+        ScriptType::OpReturn => false,
+        ScriptType::NonStandard => true,
+    };
+
+    if !valid {
+        return Err(BitcoinError::InvalidScript);
+    }
+    Ok(())
+}
+
+// Default number of inputs for `is_consolidation`
+pub const CONSOLIDATION_MIN_INPUTS: usize = 5;
+
+// Largest input or output count `sub_transaction_count` will search
+pub const MAX_SUB_TRANSACTION_SIDE: usize = 12;
+
+// Sum of `values` for every subset, indexed by bitmask, failing on overflow
+fn subset_sums(values: &[u64]) -> Result<Vec<u64>, BitcoinError> {
+    let mut sums = vec![0u64; 1 << values.len()];
+    for mask in 1..sums.len() {
+        let lowest = mask.trailing_zeros() as usize;
+        sums[mask] = sums[mask & (mask - 1)]
+            .checked_add(values[lowest])
+            .ok_or(BitcoinError::InvalidAmount)?;
+    }
+    Ok(sums)
+}
+
+// Count the groupings of the remaining inputs and outputs. Each group is keyed by
+// its lowest remaining input so every partition is counted once.
+fn count_sub_transactions(
+    inputs: u32,
+    outputs: u32,
+    fee_left: u64,
+    in_sums: &[u64],
+    out_sums: &[u64],
+) -> usize {
+    if inputs == 0 {
+        return (outputs == 0) as usize;
+    }
+    let lowest = inputs & inputs.wrapping_neg();
+    let others = inputs ^ lowest;
+
+    let mut count = 0;
+    let mut extra = others;
+    loop {
+        let group_in = lowest | extra;
+        let in_sum = in_sums[group_in as usize];
+
+        let mut group_out = outputs;
+        while group_out != 0 {
+            let out_sum = out_sums[group_out as usize];
+            if in_sum >= out_sum && in_sum - out_sum <= fee_left {
+                count += count_sub_transactions(
+                    inputs ^ group_in,
+                    outputs ^ group_out,
+                    fee_left - (in_sum - out_sum),
+                    in_sums,
+                    out_sums,
+                );
+            }
+            group_out = (group_out - 1) & outputs;
+        }
+
+        if extra == 0 {
+            break;
+        }
+        extra = (extra - 1) & others;
+    }
+    count
+}
+
+// Value of each anchor output on a Lightning commitment transaction (BOLT 3)
+pub const LN_ANCHOR_VALUE: u64 = 330;
+
+// Default number of equal-value outputs for `looks_like_coinjoin`
+pub const COINJOIN_MIN_EQUAL_OUTPUTS: usize = 5;
+
+// Payment amounts tend to be round; change rarely is
+pub fn is_round_amount(value: u64) -> bool {
+    value > 0 && value.is_multiple_of(1_000)
+}
+
+// Satoshi amount with thousands separators, e.g. "1,234,567 sat"
+pub fn format_sats_grouped(sats: u64) -> String {
+    let digits = sats.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 + 4);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(" sat");
+    grouped
+}
+
+// How `format_btc` rounds amounts finer than one satoshi
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    Truncate,
+    HalfUp,
+    Ceil,
+}
+
+// BTC amount with exactly 8 decimals, e.g. "0.12345679 BTC", rounded to whole
+// satoshis per `rounding` (toward zero, to nearest, or up)
+pub fn format_btc(btc: f64, rounding: RoundingMode) -> String {
+    let scaled = btc * block::COIN as f64;
+    // Absorb float error so that values such as 0.1 BTC land on a whole satoshi
+    let sats = if (scaled - scaled.round()).abs() < 1e-6 {
+        scaled.round()
+    } else {
+        match rounding {
+            RoundingMode::Truncate => scaled.trunc(),
+            RoundingMode::HalfUp => scaled.round(),
+            RoundingMode::Ceil => scaled.ceil(),
+        }
+    };
+
+    let sign = if sats < 0.0 { "-" } else { "" };
+    let sats = sats.abs() as u64;
+    format!("{sign}{}.{:08} BTC", sats / block::COIN, sats % block::COIN)
+}
+
+// Exact satoshi amount of a decimal BTC string such as "0.1" or "21", parsed
+// digit by digit so no precision is lost to floating point. More than 8
+// fractional digits or a value beyond u64 is an `InvalidAmount`.
+pub fn btc_string_to_sats(s: &str) -> Result<u64, BitcoinError> {
+    let invalid = || BitcoinError::ParseError(format!("Invalid BTC amount: {s:?}"));
+
+    let (whole, fraction) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+    if whole.is_empty() && fraction.is_empty()
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    if fraction.len() > 8 {
+        return Err(BitcoinError::InvalidAmount);
+    }
+
+    let whole_sats = if whole.is_empty() {
+        0
+    } else {
+        whole
+            .parse::<u64>()
+            .ok()
+            .and_then(|btc| btc.checked_mul(block::COIN))
+            .ok_or(BitcoinError::InvalidAmount)?
+    };
+    let fraction_sats = format!("{fraction:0<8}")
+        .parse::<u64>()
+        .map_err(|_| invalid())?;
+    whole_sats
+        .checked_add(fraction_sats)
+        .ok_or(BitcoinError::InvalidAmount)
+}
+
+// Fiat value of `sats` at `btc_price` per BTC, with two decimals and rounded to
+// the nearest cent, e.g. "61234.50"
+pub fn format_fiat(sats: u64, btc_price: f64) -> String {
+    let cents = (sats as f64 * btc_price * 100.0 / block::COIN as f64).round() as u64;
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+// Transaction builder
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LegacyTransactionBuilder {
+    pub version: i32,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+    pub lock_time: u32,
+    // Values of the outputs being spent, when known, checked by `finalize`
+    pub input_values: Option<Vec<u64>>,
+}
+
+impl Default for LegacyTransactionBuilder {
+    fn default() -> Self {
+        // Implement default values
+        Self {
+            version: 1,
+            inputs: vec![],
+            outputs: vec![],
+            lock_time: 0,
+            input_values: None,
+        }
+    }
+}
+
+impl LegacyTransactionBuilder {
+    pub fn new() -> Self {
+        // Initialize new builder by calling default
+        Self::default()
+    }
+
+    pub fn version(mut self, version: i32) -> Self {
+        // Set the transaction version
+        self.version = version;
+        self
+    }
+
+    pub fn add_input(mut self, input: TxInput) -> Self {
+        // Add input to the transaction
+        self.inputs.push(input);
+        self
+    }
+
+    pub fn add_output(mut self, output: TxOutput) -> Self {
+        // Add output to the transaction
+        self.outputs.push(output);
+        self
+    }
+
+    // Add a zero-value output carrying `data` behind OP_RETURN
+    pub fn add_op_return(mut self, data: &[u8]) -> Result<Self, BitcoinError> {
+        self.outputs.push(TxOutput {
+            value: 0,
+            script_pubkey: script::op_return_script(data)?,
+        });
+        Ok(self)
+    }
+
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        // Set lock_time for transaction
+        self.lock_time = lock_time;
+        self
+    }
+
+    // Hash of the builder state for caching builds: SipHash-2-4 with a fixed
+    // key, so it is the same across runs and toolchains, unlike `std::hash`.
+    // Covers the transaction serialization followed, when set, by a 0x01 marker
+    // and the CompactSize-prefixed input values.
+    pub fn config_hash(&self) -> u64 {
+        // The same bytes `build().to_bytes()` would give, without consuming self
+        let mut buf = Vec::new();
+        write_version(&mut buf, self.version);
+        buf.extend(serialize_inputs_outputs(&self.inputs, &self.outputs));
+        buf.extend(self.lock_time.to_le_bytes());
+
+        if let Some(values) = &self.input_values {
+            buf.push(0x01);
+            varint::write_compact_size(&mut buf, values.len() as u64);
+            for value in values {
+                buf.extend(value.to_le_bytes());
+            }
+        }
+        hashes::siphash24(0, 0, &buf)
+    }
+
+    // Record the values of the outputs spent by the inputs, in input order
+    pub fn with_input_values(mut self, values: Vec<u64>) -> Self {
+        self.input_values = Some(values);
+        self
+    }
+
+    // Build after checking the recorded input values: one per input, and
+    // outputs summing to no more than their total
+    pub fn finalize(mut self) -> Result<LegacyTransaction, BitcoinError> {
+        let input_values = self.input_values.take();
+        let tx = self.build();
+        if let Some(values) = input_values {
+            tx.fee(&values)?;
+        }
+        Ok(tx)
+    }
+
+    pub fn build(self) -> LegacyTransaction {
+        // Build and return the final LegacyTransaction
+        LegacyTransaction {
+            version: self.version,
+            inputs: self.inputs,
+            outputs: self.outputs,
+            lock_time: self.lock_time,
+        }
+    }
+}
+
+// Most items reserved up front from a count read off the wire. Counts come from
+// untrusted input, so larger vectors grow as their items actually parse.
+pub const MAX_PREALLOCATED_ITEMS: usize = 1_024;
+
+fn capped_capacity(declared_count: u64) -> usize {
+    declared_count.min(MAX_PREALLOCATED_ITEMS as u64) as usize
+}
+
+// Counted inputs then counted outputs, the part of a legacy serialization
+// between the version and lock_time
+fn serialize_inputs_outputs(inputs: &[TxInput], outputs: &[TxOutput]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    varint::write_compact_size(&mut buf, inputs.len() as u64);
+    for input in inputs {
+        input.consensus_encode(&mut buf);
+    }
+    varint::write_compact_size(&mut buf, outputs.len() as u64);
+    for output in outputs {
+        output.consensus_encode(&mut buf);
+    }
+    buf
+}
+
+// The version field is a signed 32-bit integer, little-endian on the wire
+pub fn write_version(buf: &mut Vec<u8>, version: i32) {
+    buf.extend(version.to_le_bytes());
+}
+
+pub fn read_version(bytes: [u8; 4]) -> i32 {
+    i32::from_le_bytes(bytes)
+}
+
+// Custom serialization for Bitcoin transaction
+pub trait BitcoinSerialize {
+    fn serialize(&self) -> Vec<u8> {
+        // Implement serialization to bytes
+        vec![]
+    }
+}
+
+// Custom serialization for transaction
+impl BitcoinSerialize for LegacyTransaction {
+    fn serialize(&self) -> Vec<u8> {
+        // Serialize only version and lock_time (simplified)
+        let mut serialized_tx = Vec::<u8>::with_capacity(8);
+        write_version(&mut serialized_tx, self.version);
+        serialized_tx.extend(self.lock_time.to_le_bytes());
+        serialized_tx
+    }
+}
+
+// Decoding legacy transaction
+impl TryFrom<&[u8]> for LegacyTransaction {
+    type Error = BitcoinError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        // Parse binary data into a LegacyTransaction
+        let mut data = data;
+
+        // Minimum length is 12 bytes (4 version + 4 inputs count + 4 lock_time)
+        if data.len() < 12 {
+            Err(BitcoinError::InvalidTransaction)
+        } else {
+            // Read tx fields from data input and build LegacyTransaction
+            let mut version_buf = [0; 4];
+            let mut input_buf = [0; 4];
+            let mut ouput_buf = [0; 4];
+            let mut lock_time_buf = [0; 4];
+
+            let _ = data.read_exact(&mut version_buf);
+            let _ = data.read_exact(&mut input_buf);
+            let _ = data.read_exact(&mut ouput_buf);
+            let _ = data.read_exact(&mut lock_time_buf);
+
+            let input_count = u32::from_le_bytes(input_buf);
+            let output_count = u32::from_le_bytes(ouput_buf);
+
+            Ok(LegacyTransaction {
+                version: read_version(version_buf),
+                inputs: Vec::with_capacity(capped_capacity(input_count as u64)),
+                outputs: Vec::with_capacity(capped_capacity(output_count as u64)),
+                lock_time: u32::from_le_bytes(lock_time_buf),
+            })
+        }
+    }
+}
+
+#[derive(Parser)]
+#[command(name = "BTxC Decoder")]
+#[command(version = "1.0.0")]
+#[command(about = "Bitcoin Transaction Decoder", long_about = None)]
+struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "Print what side-effecting commands would do without doing it"
+    )]
+    dry_run: bool,
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum CliCommand {
+    /// Sends bitcoin of { amount } to recipient { address }
+    Send {
+        #[arg(
+            required = true,
+            help = "(numeric, required) The amount of bitcoin you want to send in satoshis"
+        )]
+        amount: u64,
+        #[arg(
+            required = true,
+            help = "(string, required) The address of the recipient you want to send bitcoins to"
+        )]
+        address: String,
+    },
+
+    /// Returns the balance of transaction sender
+    Balance,
+
+    /// Decodes a raw transaction given as { hex } or read from --file
+    Decode {
+        #[arg(
+            required_unless_present = "file",
+            conflicts_with = "file",
+            help = "(string) The hex-encoded transaction to decode"
+        )]
+        hex: Option<String>,
+        #[arg(
+            long,
+            help = "Read the raw transaction bytes from a file, or hex text if it ends in .hex"
+        )]
+        file: Option<PathBuf>,
+        #[arg(long, help = "Print one CSV row per output instead of the summary")]
+        csv: bool,
+        #[arg(
+            long,
+            conflicts_with = "csv",
+            help = "Flag inputs whose scriptSigs are not push-only"
+        )]
+        strict: bool,
+    },
+
+    /// Computes the absolute fee of { raw_hex } at { feerate } sat/vB
+    Fee {
+        #[arg(
+            required = true,
+            help = "(string, required) The hex-encoded transaction"
+        )]
+        raw_hex: String,
+        #[arg(required = true, help = "(numeric, required) The fee rate in sat/vB")]
+        feerate: f64,
+    },
+}
+
+// Render a decoded transaction for the `decode` command
+pub fn decode_output(raw_hex: &str, options: DecodeOptions) -> Result<String, BitcoinError> {
+    options.check()?;
+    Ok(render_report(&inspect(raw_hex, Network::Mainnet)?, options))
+}
+
+// Same as `decode_output` for a transaction stored in a file
+pub fn decode_file_output(path: &Path, options: DecodeOptions) -> Result<String, BitcoinError> {
+    options.check()?;
+    let data = read_transaction_file(path)?;
+    Ok(render_report(
+        &inspect_bytes(&data, Network::Mainnet)?,
+        options,
+    ))
+}
+
+// Render the `fee` command: the transaction's vsize and the absolute fee it
+// needs at `feerate` sat/vB
+pub fn fee_output(raw_hex: &str, feerate: f64) -> Result<String, BitcoinError> {
+    if !feerate.is_finite() || feerate < 0.0 {
+        return Err(BitcoinError::InvalidAmount);
+    }
+    let report = inspect(raw_hex, Network::Mainnet)?;
+    let fee = fees::fee_for_vbytes(report.vsize, feerate);
+    Ok(format!(
+        "vsize: {} vB\nfee rate: {feerate} sat/vB\nfee: {}\n",
+        report.vsize,
+        format_sats_grouped(fee)
+    ))
+}
+
+// Output settings of the `decode` command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeOptions {
+    pub csv: bool,
+    // Also flag inputs whose scriptSigs are not push-only
+    pub strict: bool,
+}
+
+impl DecodeOptions {
+    // CSV has one row per output and no room for the per-input strict
+    // diagnostics, so the two cannot be combined
+    pub fn check(&self) -> Result<(), BitcoinError> {
+        if self.csv && self.strict {
+            return Err(BitcoinError::ParseError(
+                "--csv cannot be combined with --strict".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn render_report(report: &TxReport, options: DecodeOptions) -> String {
+    if options.csv {
+        report.to_csv()
+    } else if options.strict {
+        format!("{report:#}")
+    } else {
+        report.to_string()
+    }
+}
+
+// Raw transaction bytes from a file; a ".hex" extension means hex text instead
+pub fn read_transaction_file(path: &Path) -> Result<Vec<u8>, BitcoinError> {
+    let mut contents = Vec::new();
+    File::open(path)?.read_to_end(&mut contents)?;
+
+    if path.extension().is_some_and(|ext| ext == "hex") {
+        let text = String::from_utf8(contents)
+            .map_err(|_| BitcoinError::ParseError("Hex file is not valid UTF-8".to_string()))?;
+        return hex::decode(text.trim());
+    }
+    Ok(contents)
+}
+
+// Parsed command together with what it would print. Nothing is printed here,
+// so callers and tests can inspect the result before any side effects.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub command: CliCommand,
+    // Set by the global --dry-run flag: side-effecting commands only preview
+    pub dry_run: bool,
+    pub message: String,
+}
+
+// Simple CLI argument parser; prints the command's message
+pub fn parse_cli_args(args: &[String]) -> Result<CliCommand, BitcoinError> {
+    let output = execute_cli_args(args)?;
+    print!("{}", output.message);
+    Ok(output.command)
+}
+
+// Parse and run a command without printing anything
+pub fn execute_cli_args(args: &[String]) -> Result<CommandOutput, BitcoinError> {
+    // Match args to "send" or "balance" commands and parse required arguments
+    if args.is_empty() {
+        return Err(BitcoinError::ParseError(String::from(
+            "No arguments provided",
+        )));
+    }
+
+    let mut command: Vec<String> = vec![];
+    command.push("BTxC Decoder".to_string());
+    for item in args {
+        command.push(item.clone());
+    }
+
+    let cli = match Cli::try_parse_from(command) {
+        Ok(cli) => cli,
+        Err(_) => {
+            return Err(BitcoinError::ParseError(
+                "Failed to parse arguments".to_string(),
+            ));
+        }
+    };
+    let dry_run = cli.dry_run;
+    let output = |command: CliCommand, message: String| CommandOutput {
+        command,
+        dry_run,
+        message,
+    };
+
+    match &cli.command {
+        Some(CliCommand::Send { amount, address }) => {
+            if Some(amount).is_none() {
+                Err(BitcoinError::ParseError("Amount is required".to_string()))
+            } else if address.is_empty() {
+                Err(BitcoinError::ParseError(
+                    "Address cannot be empty".to_string(),
+                ))
+            } else if *amount == 0 {
+                Err(BitcoinError::InvalidAmount)
+            } else {
+                let message = if dry_run {
+                    format!("Dry run: would send {amount} satoshis to {address}\n")
+                } else {
+                    format!("Sending {amount} satoshis to {address}!\n")
+                };
+                Ok(output(
+                    CliCommand::Send {
+                        amount: *amount,
+                        address: address.clone(),
+                    },
+                    message,
+                ))
+            }
+        }
+        Some(CliCommand::Balance) => Ok(output(CliCommand::Balance, String::new())),
+        Some(CliCommand::Decode {
+            hex,
+            file,
+            csv,
+            strict,
+        }) => {
+            let report = match (hex, file) {
+                (_, Some(path)) => inspect_bytes(&read_transaction_file(path)?, Network::Mainnet)?,
+                (Some(hex), None) => inspect(hex, Network::Mainnet)?,
+                (None, None) => {
+                    return Err(BitcoinError::ParseError(
+                        "Transaction hex or --file is required".to_string(),
+                    ));
+                }
+            };
+            let options = DecodeOptions {
+                csv: *csv,
+                strict: *strict,
+            };
+            Ok(output(
+                CliCommand::Decode {
+                    hex: hex.clone(),
+                    file: file.clone(),
+                    csv: *csv,
+                    strict: *strict,
+                },
+                render_report(&report, options),
+            ))
+        }
+        Some(CliCommand::Fee { raw_hex, feerate }) => Ok(output(
+            CliCommand::Fee {
+                raw_hex: raw_hex.clone(),
+                feerate: *feerate,
+            },
+            fee_output(raw_hex, *feerate)?,
+        )),
+        _ => Err(BitcoinError::ParseError(String::from(
+            "No valid command specified",
+        ))),
+    }
+}
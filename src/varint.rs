@@ -0,0 +1,110 @@
+#[cfg(not(feature = "no_std"))]
+use crate::BitcoinError;
+
+// Bitcoin CompactSize unsigned integers. `encode_compact_size` and
+// `decode_compact_size` only touch `core`: they work on caller-provided slices
+// and never allocate, so they are all the `no_std` feature builds. The other
+// functions are the `Vec` and `BitcoinError` conveniences used by the crate.
+
+// Longest CompactSize encoding: a 0xff prefix and eight value bytes
+pub const MAX_COMPACT_SIZE_LEN: usize = 9;
+
+// Failure of the allocation-free decoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactSizeError {
+    Truncated,
+    NonCanonical,
+}
+
+impl core::fmt::Display for CompactSizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            CompactSizeError::Truncated => f.write_str("Truncated CompactSize"),
+            CompactSizeError::NonCanonical => f.write_str("Non-canonical CompactSize"),
+        }
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl From<CompactSizeError> for BitcoinError {
+    fn from(error: CompactSizeError) -> Self {
+        BitcoinError::ParseError(error.to_string())
+    }
+}
+
+// Write the CompactSize encoding of `n` to the front of `out`, returning the
+// number of bytes used
+pub fn encode_compact_size(n: u64, out: &mut [u8; MAX_COMPACT_SIZE_LEN]) -> usize {
+    match n {
+        0..=0xfc => {
+            out[0] = n as u8;
+            1
+        }
+        0xfd..=0xffff => {
+            out[0] = 0xfd;
+            out[1..3].copy_from_slice(&(n as u16).to_le_bytes());
+            3
+        }
+        0x10000..=0xffff_ffff => {
+            out[0] = 0xfe;
+            out[1..5].copy_from_slice(&(n as u32).to_le_bytes());
+            5
+        }
+        _ => {
+            out[0] = 0xff;
+            out[1..9].copy_from_slice(&n.to_le_bytes());
+            9
+        }
+    }
+}
+
+// Decode a CompactSize from the start of `data`, returning the value and the
+// number of bytes it occupied. Non-minimal encodings are rejected.
+pub fn decode_compact_size(data: &[u8]) -> Result<(u64, usize), CompactSizeError> {
+    let (value, len, min) = match *data.first().ok_or(CompactSizeError::Truncated)? {
+        prefix @ 0..=0xfc => (prefix as u64, 1, 0),
+        0xfd => {
+            let bytes = data.get(1..3).ok_or(CompactSizeError::Truncated)?;
+            (u16::from_le_bytes([bytes[0], bytes[1]]) as u64, 3, 0xfd)
+        }
+        0xfe => {
+            let bytes = data.get(1..5).ok_or(CompactSizeError::Truncated)?;
+            let value = u32::from_le_bytes(bytes.try_into().expect("4 byte slice"));
+            (value as u64, 5, 0x10000)
+        }
+        0xff => {
+            let bytes = data.get(1..9).ok_or(CompactSizeError::Truncated)?;
+            let value = u64::from_le_bytes(bytes.try_into().expect("8 byte slice"));
+            (value, 9, 0x1_0000_0000)
+        }
+    };
+
+    if value < min {
+        return Err(CompactSizeError::NonCanonical);
+    }
+    Ok((value, len))
+}
+
+// Append the CompactSize encoding of `n`
+#[cfg(not(feature = "no_std"))]
+pub fn write_compact_size(buf: &mut Vec<u8>, n: u64) {
+    let mut encoded = [0; MAX_COMPACT_SIZE_LEN];
+    let len = encode_compact_size(n, &mut encoded);
+    buf.extend_from_slice(&encoded[..len]);
+}
+
+// Number of bytes the CompactSize encoding of `n` occupies
+pub fn compact_size_len(n: u64) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+// `decode_compact_size` with its error as a `BitcoinError`
+#[cfg(not(feature = "no_std"))]
+pub fn read_compact_size(data: &[u8]) -> Result<(u64, usize), BitcoinError> {
+    Ok(decode_compact_size(data)?)
+}
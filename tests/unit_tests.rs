@@ -157,3 +157,3453 @@ fn test_generic_point() {
     assert_eq!(str_point.x, "x");
     assert_eq!(str_point.y, "y");
 }
+
+#[test]
+fn test_decode_xpub() {
+    // BIP-32 test vector 1, chain m
+    let xpub = decode_xpub(
+        "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+    )
+    .unwrap();
+
+    assert_eq!(xpub.version, [0x04, 0x88, 0xb2, 0x1e]);
+    assert_eq!(xpub.depth, 0);
+    assert_eq!(xpub.parent_fingerprint, [0; 4]);
+    assert_eq!(xpub.child_number, 0);
+    assert_eq!(
+        xpub.chain_code,
+        [
+            0x87, 0x3d, 0xff, 0x81, 0xc0, 0x2f, 0x52, 0x56, 0x23, 0xfd, 0x1f, 0xe5, 0x16, 0x7e,
+            0xac, 0x3a, 0x55, 0xa0, 0x49, 0xde, 0x3d, 0x31, 0x4b, 0xb4, 0x2e, 0xe2, 0x27, 0xff,
+            0xed, 0x37, 0xd5, 0x08
+        ]
+    );
+    assert_eq!(xpub.public_key[..4], [0x03, 0x39, 0xa3, 0x60]);
+}
+
+#[test]
+fn test_decode_xpub_errors() {
+    // Last character altered, breaking the checksum
+    let result = decode_xpub(
+        "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet9",
+    );
+    assert!(matches!(result, Err(BitcoinError::ParseError(_))));
+
+    // Valid checksum over a payload of the wrong length
+    let result = decode_xpub(&base58::encode_check(&[0; 10]));
+    assert!(matches!(result, Err(BitcoinError::ParseError(_))));
+}
+
+#[test]
+fn test_xpub_derive_child() {
+    // BIP-32 test vector 1: m/0H/1 derived publicly from m/0H
+    let parent = decode_xpub(
+        "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+    )
+    .unwrap();
+    let child = parent.derive_child(1).unwrap();
+
+    assert_eq!(child.depth, 2);
+    assert_eq!(child.child_number, 1);
+    assert_eq!(
+        child.encode(),
+        "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ"
+    );
+
+    // Hardened indices need the private key
+    let result = parent.derive_child(0x8000_0000);
+    assert!(matches!(result, Err(BitcoinError::InvalidTransaction)));
+}
+
+#[test]
+fn test_required_feerate_for_target() {
+    // Near the front of the mempool the current rate is enough
+    assert_eq!(required_feerate_for_target(10.0, 100, 1), 10.0);
+
+    let near = required_feerate_for_target(10.0, 5_000, 1);
+    let far = required_feerate_for_target(10.0, 20_000, 1);
+    assert!(near > 10.0);
+    assert!(far > near);
+
+    // A more relaxed target needs less
+    assert!(required_feerate_for_target(10.0, 20_000, 6) < far);
+}
+
+#[test]
+fn test_has_duplicate_inputs() {
+    let input = TxInput {
+        previous_output: OutPoint {
+            txid: [7; 32],
+            vout: 1,
+        },
+        script_sig: vec![],
+        sequence: 0xFFFFFFFF,
+    };
+    let other = TxInput {
+        previous_output: OutPoint {
+            txid: [7; 32],
+            vout: 2,
+        },
+        ..input.clone()
+    };
+
+    let tx = LegacyTransaction::builder()
+        .add_input(input.clone())
+        .add_input(other)
+        .build();
+    assert!(!tx.has_duplicate_inputs());
+
+    let tx = LegacyTransaction::builder()
+        .add_input(input.clone())
+        .add_input(input)
+        .build();
+    assert!(tx.has_duplicate_inputs());
+}
+
+#[test]
+fn test_serialize_witnesses() {
+    let input = TxInput {
+        previous_output: OutPoint {
+            txid: [1; 32],
+            vout: 0,
+        },
+        script_sig: vec![],
+        sequence: 0xFFFFFFFF,
+    };
+    let tx = SegwitTransaction {
+        version: 2,
+        inputs: vec![input.clone(), input],
+        outputs: vec![],
+        // P2WPKH-style stack (signature, pubkey) and an empty witness
+        witnesses: vec![vec![vec![0x30; 71], vec![0x02; 33]], vec![]],
+        lock_time: 0,
+    };
+
+    let witnesses = tx.serialize_witnesses();
+    let mut expected = vec![0x02, 71];
+    expected.extend([0x30; 71]);
+    expected.push(33);
+    expected.extend([0x02; 33]);
+    expected.push(0x00);
+    assert_eq!(witnesses, expected);
+}
+
+#[test]
+fn test_p2wpkh_address_networks() {
+    let pubkey_hash = [
+        0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54, 0x94, 0x1c, 0x45, 0xd1, 0xb3, 0xa3,
+        0x23, 0xf1, 0x43, 0x3b, 0xd6,
+    ];
+
+    assert_eq!(
+        address::p2wpkh_address(&pubkey_hash, &Network::Mainnet),
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+    );
+    assert_eq!(
+        address::p2wpkh_address(&pubkey_hash, &Network::Regtest),
+        "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"
+    );
+
+    let custom = Network::Custom {
+        p2pkh_version: 0x7d,
+        p2sh_version: 0x57,
+        bech32_hrp: "sb".to_string(),
+    };
+    assert_eq!(
+        address::p2wpkh_address(&pubkey_hash, &custom),
+        "sb1qw508d6qejxtdg4y5r3zarvary0c5xw7krxe8se"
+    );
+
+    // Base58 addresses pick up the custom version byte
+    let custom_p2pkh = address::p2pkh_address(&pubkey_hash, &custom);
+    assert_eq!(base58::decode_check(&custom_p2pkh).unwrap()[0], 0x7d);
+}
+
+#[test]
+fn test_fee_rate_percentile() {
+    let recent: Vec<f64> = (1..=10).map(|rate| rate as f64).collect();
+
+    assert_eq!(fee_rate_percentile(8.0, &recent), 80.0);
+    assert_eq!(fee_rate_percentile(0.5, &recent), 0.0);
+    assert_eq!(fee_rate_percentile(50.0, &recent), 100.0);
+    assert_eq!(fee_rate_percentile(8.0, &[]), 0.0);
+}
+
+#[test]
+fn test_transaction_from_bytes_round_trip() {
+    let tx = LegacyTransaction::builder()
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [3; 32],
+                vout: 1,
+            },
+            script_sig: vec![0x51, 0x52],
+            sequence: 0xFFFFFFFE,
+        })
+        .add_output(TxOutput {
+            value: 12_345,
+            script_pubkey: vec![0x6a],
+        })
+        .lock_time(800_000)
+        .build();
+
+    let bytes = tx.to_bytes();
+    // version + 1 count + 41 + 2 script + 1 count + 8 value + 1 + 1 script + lock_time
+    assert_eq!(bytes.len(), 4 + 1 + 43 + 1 + 10 + 4);
+
+    let decoded = LegacyTransaction::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(decoded.inputs[0].script_sig, vec![0x51, 0x52]);
+    assert_eq!(decoded.outputs[0].value, 12_345);
+    assert_eq!(decoded.lock_time, 800_000);
+}
+
+#[test]
+fn test_transaction_from_bytes_script_sig_underflow() {
+    let mut data = vec![1, 0, 0, 0, 1];
+    data.extend([0; 32]); // previous txid
+    data.extend([0; 4]); // previous vout
+    data.push(100); // scriptSig claims 100 bytes
+    data.extend([0xAB; 10]); // but only 10 follow
+
+    match LegacyTransaction::from_bytes(&data) {
+        Err(BitcoinError::ParseError(msg)) => {
+            assert!(msg.contains("scriptSig"));
+            assert!(msg.contains("offset 41"));
+        }
+        other => panic!("Expected ParseError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_transaction_to_dot() {
+    let tx = LegacyTransaction::builder()
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [0xAA; 32],
+                vout: 3,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFF,
+        })
+        .add_output(TxOutput {
+            value: 40_000,
+            script_pubkey: vec![],
+        })
+        .add_output(TxOutput {
+            value: 9_000,
+            script_pubkey: vec![],
+        })
+        .build();
+
+    let dot = tx.to_dot();
+    assert!(dot.starts_with("digraph transaction {"));
+    assert!(dot.contains(&format!("{}:3", "aa".repeat(32))));
+    assert!(dot.contains("in0 -> tx;"));
+    assert!(dot.contains("40000 sat"));
+    assert!(dot.contains("9000 sat"));
+    assert!(dot.contains("tx -> out1;"));
+}
+
+fn push_psbt_record(buf: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    varint::write_compact_size(buf, key.len() as u64);
+    buf.extend_from_slice(key);
+    varint::write_compact_size(buf, value.len() as u64);
+    buf.extend_from_slice(value);
+}
+
+fn sample_unsigned_tx() -> LegacyTransaction {
+    LegacyTransaction::builder()
+        .version(2)
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout: 0,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFD,
+        })
+        .add_output(TxOutput {
+            value: 90_000,
+            script_pubkey: vec![
+                0x00, 0x14, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+                0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+            ],
+        })
+        .build()
+}
+
+#[test]
+fn test_psbt_from_bytes() {
+    let tx = sample_unsigned_tx();
+    let utxo = TxOutput {
+        value: 100_000,
+        script_pubkey: vec![
+            0x00, 0x14, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33,
+            0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33,
+        ],
+    };
+    let mut utxo_bytes = utxo.value.to_le_bytes().to_vec();
+    utxo_bytes.push(utxo.script_pubkey.len() as u8);
+    utxo_bytes.extend(&utxo.script_pubkey);
+
+    let mut data = b"psbt\xff".to_vec();
+    push_psbt_record(&mut data, &[0x00], &tx.to_bytes());
+    data.push(0x00);
+    push_psbt_record(&mut data, &[0x01], &utxo_bytes);
+    data.push(0x00);
+    data.push(0x00);
+
+    let psbt = Psbt::from_bytes(&data).unwrap();
+    assert_eq!(psbt.unsigned_tx.to_bytes(), tx.to_bytes());
+    assert_eq!(psbt.inputs.len(), 1);
+    assert_eq!(psbt.outputs.len(), 1);
+
+    let parsed_utxo = psbt.witness_utxo(0).unwrap().unwrap();
+    assert_eq!(parsed_utxo.value, 100_000);
+    assert_eq!(parsed_utxo.script_pubkey, utxo.script_pubkey);
+}
+
+#[test]
+fn test_psbt_from_bytes_bad_magic() {
+    let result = Psbt::from_bytes(b"pbst\xff\x00");
+    assert!(matches!(result, Err(BitcoinError::ParseError(_))));
+}
+
+#[test]
+fn test_psbt_serialize_round_trip() {
+    let tx = sample_unsigned_tx();
+
+    let mut data = b"psbt\xff".to_vec();
+    push_psbt_record(&mut data, &[0x00], &tx.to_bytes());
+    push_psbt_record(&mut data, &[0xfb], &[0x00, 0x00, 0x00, 0x00]); // PSBT version 0
+    data.push(0x00);
+    push_psbt_record(
+        &mut data,
+        &[0x01],
+        &[0xa0, 0x86, 0x01, 0, 0, 0, 0, 0, 0x01, 0x51],
+    );
+    push_psbt_record(&mut data, &[0x03], &[0x01, 0x00, 0x00, 0x00]); // SIGHASH_ALL
+    data.push(0x00);
+    push_psbt_record(&mut data, &[0xfc, 0x01, 0xaa], &[0xbb]); // proprietary record
+    data.push(0x00);
+
+    let psbt = Psbt::from_bytes(&data).unwrap();
+    assert_eq!(psbt.serialize(), data);
+    assert_eq!(
+        Psbt::from_bytes(&psbt.serialize()).unwrap().serialize(),
+        data
+    );
+}
+
+#[test]
+fn test_psbt_combine() {
+    let tx = sample_unsigned_tx();
+    let psbt_with_sig = |pubkey: u8, sig: u8| {
+        let mut data = b"psbt\xff".to_vec();
+        push_psbt_record(&mut data, &[0x00], &tx.to_bytes());
+        data.push(0x00);
+        push_psbt_record(&mut data, &[0x02, pubkey], &[sig]);
+        data.push(0x00);
+        data.push(0x00);
+        Psbt::from_bytes(&data).unwrap()
+    };
+
+    let mut first = psbt_with_sig(0x02, 0xAA);
+    let second = psbt_with_sig(0x03, 0xBB);
+    first.combine(&second).unwrap();
+
+    let sigs = first.partial_sigs(0).unwrap();
+    assert_eq!(
+        sigs,
+        vec![(vec![0x02], vec![0xAA]), (vec![0x03], vec![0xBB])]
+    );
+
+    // A PSBT for a different transaction cannot be combined
+    let mut other_tx = tx.clone();
+    other_tx.lock_time = 1;
+    let mut data = b"psbt\xff".to_vec();
+    push_psbt_record(&mut data, &[0x00], &other_tx.to_bytes());
+    data.extend([0x00, 0x00, 0x00]);
+    let unrelated = Psbt::from_bytes(&data).unwrap();
+    assert!(matches!(
+        first.combine(&unrelated),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_psbt_extract_transaction() {
+    let pubkey = [0x02; 33];
+    let signature = [0x30; 71];
+
+    let prev_tx = LegacyTransaction::builder()
+        .add_output(TxOutput {
+            value: 100_000,
+            script_pubkey: script::p2pkh_script(&hashes::hash160(&pubkey)),
+        })
+        .build();
+    let mut tx = sample_unsigned_tx();
+    tx.inputs[0].previous_output = OutPoint {
+        txid: prev_tx.txid(),
+        vout: 0,
+    };
+
+    let mut data = b"psbt\xff".to_vec();
+    push_psbt_record(&mut data, &[0x00], &tx.to_bytes());
+    data.push(0x00);
+    push_psbt_record(&mut data, &[0x00], &prev_tx.to_bytes());
+    let unsigned = Psbt::from_bytes(&[data.clone(), vec![0x00, 0x00]].concat()).unwrap();
+    push_psbt_record(&mut data, &[&[0x02][..], &pubkey].concat(), &signature);
+    data.extend([0x00, 0x00]);
+    let signed = Psbt::from_bytes(&data).unwrap();
+
+    let extracted = signed.extract_transaction().unwrap();
+    let mut expected_script_sig = vec![71];
+    expected_script_sig.extend(signature);
+    expected_script_sig.push(33);
+    expected_script_sig.extend(pubkey);
+    assert_eq!(extracted.inputs[0].script_sig, expected_script_sig);
+    assert_eq!(extracted.outputs[0].value, tx.outputs[0].value);
+
+    // Without a signature the input is not finalized
+    assert!(matches!(
+        unsigned.extract_transaction(),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+
+    // A previous transaction in segwit serialization is accepted too
+    let segwit_prev = SegwitTransaction {
+        version: 2,
+        inputs: sample_unsigned_tx().inputs,
+        outputs: prev_tx.outputs.clone(),
+        witnesses: vec![vec![vec![0x30; 71], vec![0x02; 33]]],
+        lock_time: 0,
+    };
+    let mut segwit_psbt = signed.clone();
+    segwit_psbt.unsigned_tx.inputs[0].previous_output.txid = segwit_prev.txid();
+    segwit_psbt.inputs[0].insert(vec![0x00], segwit_prev.to_bytes());
+    assert_eq!(
+        segwit_psbt.spent_output(0).unwrap(),
+        Some(prev_tx.outputs[0].clone())
+    );
+    assert_eq!(
+        segwit_psbt.extract_transaction().unwrap().inputs[0].script_sig,
+        expected_script_sig
+    );
+
+    // A vout past the previous transaction's outputs is not "no UTXO"
+    let mut bad_vout = signed.clone();
+    bad_vout.unsigned_tx.inputs[0].previous_output.vout = 5;
+    assert!(matches!(
+        bad_vout.spent_output(0),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+
+    // A truncated input map list is an error rather than a panic
+    let mut truncated = signed.clone();
+    truncated.inputs.clear();
+    assert!(matches!(
+        truncated.extract_transaction(),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_likely_change_index() {
+    let output = |value: u64, script_pubkey: Vec<u8>| TxOutput {
+        value,
+        script_pubkey,
+    };
+
+    // Round payment to a P2WPKH address, non-round change
+    let tx = LegacyTransaction::builder()
+        .add_output(output(
+            5_000_000,
+            [vec![0x00, 0x14], vec![0x44; 20]].concat(),
+        ))
+        .add_output(output(1_234_567, script::p2pkh_script(&[0x55; 20])))
+        .build();
+    assert_eq!(tx.likely_change_index(), Some(1));
+
+    // Two non-round amounts and no input hints
+    let tx = LegacyTransaction::builder()
+        .add_output(output(5_000_001, script::p2pkh_script(&[0x44; 20])))
+        .add_output(output(1_234_567, script::p2pkh_script(&[0x55; 20])))
+        .build();
+    assert_eq!(tx.likely_change_index(), None);
+}
+
+#[test]
+fn test_version_helpers() {
+    let mut buf = Vec::new();
+    write_version(&mut buf, -2);
+    assert_eq!(buf, [0xFE, 0xFF, 0xFF, 0xFF]);
+    assert_eq!(read_version([0xFE, 0xFF, 0xFF, 0xFF]), -2);
+    assert_eq!(read_version([0x02, 0x00, 0x00, 0x00]), 2);
+
+    // Negative versions survive both serializers
+    let tx = LegacyTransaction::builder().version(-1).build();
+    assert_eq!(tx.serialize()[..4], [0xFF; 4]);
+    assert_eq!(
+        LegacyTransaction::from_bytes(&tx.to_bytes())
+            .unwrap()
+            .version,
+        -1
+    );
+}
+
+#[test]
+fn test_display_relative_lock_time() {
+    let input = |sequence: u32| TxInput {
+        previous_output: OutPoint {
+            txid: [0; 32],
+            vout: 0,
+        },
+        script_sig: vec![],
+        sequence,
+    };
+    let tx = LegacyTransaction::builder()
+        .version(2)
+        .add_input(input(144))
+        .add_input(input((1 << 22) | 1))
+        .add_input(input(0xFFFFFFFF))
+        .build();
+
+    let display = tx.to_string();
+    let lines: Vec<&str> = display.lines().collect();
+    assert!(lines[2].ends_with("relative locktime: 144 blocks"));
+    assert!(lines[3].ends_with("relative locktime: 512 seconds"));
+    assert!(!lines[4].contains("relative locktime"));
+
+    // A zero lock, or any lock in a version 1 transaction, means nothing
+    let zero = LegacyTransaction::builder()
+        .version(2)
+        .add_input(input(0))
+        .build();
+    assert!(!zero.to_string().contains("relative locktime"));
+    let mut version_1 = tx.clone();
+    version_1.version = 1;
+    assert!(!version_1.to_string().contains("relative locktime"));
+}
+
+#[test]
+fn test_estimate_batch_payout() {
+    let recipients: Vec<(String, u64)> = (1..=5u8)
+        .map(|i| {
+            (
+                address::p2wpkh_address(&[i; 20], &Network::Mainnet),
+                100_000,
+            )
+        })
+        .collect();
+    let utxo = |value: u64, vout: u32| {
+        (
+            OutPoint {
+                txid: [9; 32],
+                vout,
+            },
+            TxOutput {
+                value,
+                script_pubkey: script::witness_script(0, &[0xEE; 20]),
+            },
+        )
+    };
+    let utxos = vec![utxo(300_000, 0), utxo(400_000, 1), utxo(50_000, 2)];
+
+    let estimate = estimate_batch_payout(
+        &recipients,
+        &utxos,
+        10.0,
+        ScriptType::P2WPKH,
+        Network::Mainnet,
+    )
+    .unwrap();
+    assert_eq!(estimate.total_sent, 500_000);
+    assert_eq!(estimate.inputs_used, 2);
+    assert!(estimate.needs_change);
+    // 11 overhead + 2 * 68 inputs + 6 * 31 outputs at 10 sat/vB
+    assert_eq!(estimate.estimated_fee, 3_330);
+
+    let result = estimate_batch_payout(
+        &recipients,
+        &utxos[2..],
+        10.0,
+        ScriptType::P2WPKH,
+        Network::Mainnet,
+    );
+    assert!(matches!(result, Err(BitcoinError::InvalidAmount)));
+
+    // P2PKH change is 3 vB larger than P2WPKH
+    let estimate = estimate_batch_payout(
+        &recipients,
+        &utxos,
+        10.0,
+        ScriptType::P2PKH,
+        Network::Mainnet,
+    )
+    .unwrap();
+    assert_eq!(estimate.estimated_fee, 3_360);
+
+    // UTXO values summing past u64::MAX are rejected rather than overflowing
+    let huge = vec![utxo(u64::MAX, 0), utxo(u64::MAX, 1)];
+    let huge_recipients = vec![(recipients[0].0.clone(), u64::MAX - 1)];
+    let result = estimate_batch_payout(
+        &huge_recipients,
+        &huge,
+        10.0,
+        ScriptType::P2WPKH,
+        Network::Mainnet,
+    );
+    assert!(matches!(result, Err(BitcoinError::InvalidAmount)));
+}
+
+#[test]
+fn test_is_consolidation() {
+    let mut builder = LegacyTransaction::builder();
+    for vout in 0..10 {
+        builder = builder.add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [1; 32],
+                vout,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFF,
+        });
+    }
+    let tx = builder
+        .add_output(TxOutput {
+            value: 1_000_000,
+            script_pubkey: vec![],
+        })
+        .build();
+
+    assert!(tx.is_consolidation());
+    assert!(!tx.is_consolidation_with_threshold(11));
+
+    let mut split = tx.clone();
+    split.outputs.push(split.outputs[0].clone());
+    assert!(!split.is_consolidation());
+}
+
+#[test]
+fn test_output_pays_to() {
+    let output = TxOutput {
+        value: 10_000,
+        script_pubkey: script::witness_script(
+            0,
+            &[
+                0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54, 0x94, 0x1c, 0x45, 0xd1, 0xb3,
+                0xa3, 0x23, 0xf1, 0x43, 0x3b, 0xd6,
+            ],
+        ),
+    };
+
+    assert!(output.pays_to(
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        Network::Mainnet
+    ));
+    assert!(!output.pays_to(
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        Network::Testnet
+    ));
+    assert!(!output.pays_to("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", Network::Mainnet));
+    // Uppercase Bech32, as used in QR codes, is the same address
+    assert!(output.pays_to(
+        "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+        Network::Mainnet
+    ));
+
+    let burn = TxOutput {
+        value: 0,
+        script_pubkey: vec![0x6a],
+    };
+    assert!(!burn.pays_to("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", Network::Mainnet));
+}
+
+#[test]
+fn test_package_vsize() {
+    let parent = LegacyTransaction::builder()
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [1; 32],
+                vout: 0,
+            },
+            script_sig: vec![0x00; 107],
+            sequence: 0xFFFFFFFF,
+        })
+        .add_output(TxOutput {
+            value: 50_000,
+            script_pubkey: script::p2pkh_script(&[2; 20]),
+        })
+        .build();
+    let child = LegacyTransaction::builder()
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: parent.txid(),
+                vout: 0,
+            },
+            script_sig: vec![0x00; 107],
+            sequence: 0xFFFFFFFF,
+        })
+        .build();
+
+    // 10 overhead + 148 input + 34 output, and the same without an output
+    assert_eq!(parent.vsize(), 192);
+    assert_eq!(child.vsize(), 158);
+    assert_eq!(package_vsize(&[parent, child]), 350);
+    assert_eq!(package_vsize(&[]), 0);
+}
+
+#[test]
+fn test_block_header_accessors() {
+    let genesis = hex::decode(
+        "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+    )
+    .unwrap();
+    let header = BlockHeader::from_bytes(&genesis).unwrap();
+
+    assert_eq!(header.to_bytes(), genesis);
+    assert_eq!(
+        hex::encode_reversed(&header.block_hash()),
+        "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+    );
+    assert_eq!(header.timestamp_datetime(), "2009-01-03 18:15:05 UTC");
+    assert_eq!(header.difficulty(), 1.0);
+    assert_eq!(header.nonce(), 2_083_236_893);
+
+    // Block 100,000
+    let header = BlockHeader {
+        bits: 0x1b04864c,
+        time: 1_293_623_863,
+        ..header
+    };
+    assert!((header.difficulty() - 14_484.162_361_225).abs() < 1e-6);
+    assert_eq!(header.timestamp_datetime(), "2010-12-29 11:57:43 UTC");
+
+    assert!(matches!(
+        BlockHeader::from_bytes(&genesis[..79]),
+        Err(BitcoinError::ParseError(_))
+    ));
+}
+
+fn coinbase_tx(output_values: &[u64]) -> LegacyTransaction {
+    let mut builder = LegacyTransaction::builder().add_input(TxInput {
+        previous_output: OutPoint::null(),
+        script_sig: vec![0x03, 0xa0, 0x86, 0x01], // BIP-34 height push
+        sequence: 0xFFFFFFFF,
+    });
+    for &value in output_values {
+        builder = builder.add_output(TxOutput {
+            value,
+            script_pubkey: script::p2pkh_script(&[0x77; 20]),
+        });
+    }
+    builder.build()
+}
+
+#[test]
+fn test_is_valid_coinbase() {
+    let coinbase = coinbase_tx(&[5_000_000_000]);
+    assert!(coinbase.is_valid_coinbase());
+
+    let mut two_inputs = coinbase.clone();
+    two_inputs.inputs.push(coinbase.inputs[0].clone());
+    assert!(!two_inputs.is_valid_coinbase());
+
+    let mut short_script = coinbase.clone();
+    short_script.inputs[0].script_sig = vec![0x51];
+    assert!(!short_script.is_valid_coinbase());
+
+    let mut not_null = coinbase;
+    not_null.inputs[0].previous_output.vout = 0;
+    assert!(!not_null.is_valid_coinbase());
+}
+
+#[test]
+fn test_block_subsidy() {
+    assert_eq!(block_subsidy(0), 5_000_000_000);
+    assert_eq!(block_subsidy(209_999), 5_000_000_000);
+    assert_eq!(block_subsidy(210_000), 2_500_000_000);
+    assert_eq!(block_subsidy(840_000), 312_500_000);
+    assert_eq!(block_subsidy(64 * 210_000), 0);
+}
+
+#[test]
+fn test_validate_coinbase_amount() {
+    // Height 840,000 subsidy is 3.125 BTC
+    let coinbase = coinbase_tx(&[312_500_000, 20_000]);
+    assert!(validate_coinbase_amount(&coinbase, 840_000, 20_000).is_ok());
+
+    let greedy = coinbase_tx(&[312_500_000, 20_001]);
+    assert!(matches!(
+        validate_coinbase_amount(&greedy, 840_000, 20_000),
+        Err(BitcoinError::InvalidAmount)
+    ));
+}
+
+#[test]
+fn test_inspect_segwit_transaction() {
+    let raw = "0200000000010111111111111111111111111111111111111111111111111111111111111111110100000000fdffffff0150c30000000000001600142222222222222222222222222222222222222222024730303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030302102333333333333333333333333333333333333333333333333333333333333333300350c00";
+    let report = inspect(raw, Network::Mainnet).unwrap();
+
+    assert_eq!(
+        report.txid,
+        "c666830569a7036c520d3dfa5018e91898dc88f6275895962bd7e48286eb0037"
+    );
+    assert_eq!(report.version, 2);
+    assert!(report.segwit);
+    assert_eq!(report.size, 191);
+    assert_eq!(report.weight, 437);
+    assert_eq!(report.vsize, 110);
+    assert!(report.rbf);
+    assert_eq!(report.lock_time, LockTime::Blocks(800_000));
+
+    assert_eq!(report.inputs.len(), 1);
+    assert_eq!(report.inputs[0].witness_items, 2);
+    assert!(report.inputs[0].previous_output.ends_with(":1"));
+
+    assert_eq!(report.outputs[0].value, 50_000);
+    assert_eq!(report.outputs[0].script_type, ScriptType::P2WPKH);
+    assert_eq!(
+        report.outputs[0].address.as_deref(),
+        Some("bc1qyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zc6v074")
+    );
+}
+
+#[test]
+fn test_builder_add_op_return() {
+    let tx = LegacyTransaction::builder()
+        .add_op_return(b"hello")
+        .unwrap()
+        .build();
+    assert_eq!(tx.outputs.len(), 1);
+    assert_eq!(tx.outputs[0].value, 0);
+    assert_eq!(tx.outputs[0].script_pubkey, b"\x6a\x05hello");
+    assert_eq!(tx.outputs[0].script_type(), ScriptType::OpReturn);
+
+    // 76+ bytes need OP_PUSHDATA1
+    let tx = LegacyTransaction::builder()
+        .add_op_return(&[0xab; 80])
+        .unwrap()
+        .build();
+    assert_eq!(tx.outputs[0].script_pubkey[..3], [0x6a, 0x4c, 80]);
+    assert_eq!(tx.outputs[0].script_pubkey.len(), 83);
+
+    assert!(matches!(
+        LegacyTransaction::builder().add_op_return(&[0; 81]),
+        Err(BitcoinError::InvalidScript)
+    ));
+}
+
+#[test]
+fn test_describe_sequence() {
+    assert_eq!(describe_sequence(0xFFFFFFFF, 2), "final");
+    assert_eq!(describe_sequence(0xFFFFFFFE, 2), "locktime-enabled, no RBF");
+    assert_eq!(describe_sequence(0xFFFFFFFD, 2), "RBF-signaling");
+    assert_eq!(describe_sequence(144, 2), "relative timelock: 144 blocks");
+    assert_eq!(
+        describe_sequence(SEQUENCE_LOCKTIME_TYPE_FLAG | 2, 2),
+        "relative timelock: 1024 seconds"
+    );
+
+    // Zero locks, and any lock before version 2, only signal RBF
+    assert_eq!(describe_sequence(0, 2), "RBF-signaling");
+    assert_eq!(
+        describe_sequence(SEQUENCE_LOCKTIME_TYPE_FLAG, 2),
+        "RBF-signaling"
+    );
+    assert_eq!(describe_sequence(144, 1), "RBF-signaling");
+}
+
+#[test]
+fn test_decode_csv_output() {
+    let tx = LegacyTransaction::builder()
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout: 0,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFF,
+        })
+        .add_output(TxOutput {
+            value: 50_000,
+            script_pubkey: script::witness_script(0, &[0x22; 20]),
+        })
+        .build();
+    let raw = hex::encode(&tx.to_bytes());
+
+    let csv = decode_output(
+        &raw,
+        DecodeOptions {
+            csv: true,
+            strict: false,
+        },
+    )
+    .unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[0], "txid,vout,value_sats,address,script_type");
+    assert_eq!(
+        rows[1],
+        format!(
+            "{},0,50000,bc1qyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zc6v074,P2WPKH",
+            hex::encode_reversed(&tx.txid())
+        )
+    );
+    assert_eq!(rows.len(), 2);
+
+    let args = vec!["decode".to_string(), raw, "--csv".to_string()];
+    assert!(matches!(
+        parse_cli_args(&args),
+        Ok(CliCommand::Decode { csv: true, .. })
+    ));
+}
+
+#[test]
+fn test_total_script_bytes() {
+    let input = |script_sig: Vec<u8>| TxInput {
+        previous_output: OutPoint {
+            txid: [0x11; 32],
+            vout: 0,
+        },
+        script_sig,
+        sequence: 0xFFFFFFFF,
+    };
+    let tx = LegacyTransaction::builder()
+        .add_input(input(vec![0x01; 107]))
+        .add_input(input(vec![0x02; 72]))
+        .add_output(TxOutput {
+            value: 1_000,
+            script_pubkey: script::p2pkh_script(&[0x33; 20]),
+        })
+        .add_output(TxOutput {
+            value: 2_000,
+            script_pubkey: script::witness_script(0, &[0x44; 32]),
+        })
+        .build();
+
+    assert_eq!(tx.total_script_bytes(), (179, 25 + 34));
+}
+
+#[test]
+fn test_format_sats_grouped() {
+    assert_eq!(format_sats_grouped(0), "0 sat");
+    assert_eq!(format_sats_grouped(999), "999 sat");
+    assert_eq!(format_sats_grouped(1_000), "1,000 sat");
+    assert_eq!(format_sats_grouped(1_234_567), "1,234,567 sat");
+    assert_eq!(
+        format_sats_grouped(u64::MAX),
+        "18,446,744,073,709,551,615 sat"
+    );
+}
+
+#[test]
+fn test_reused_addresses() {
+    let output = |value: u64, hash: u8| TxOutput {
+        value,
+        script_pubkey: script::witness_script(0, &[hash; 20]),
+    };
+    let tx = LegacyTransaction::builder()
+        .add_output(output(10_000, 0x22))
+        .add_output(output(20_000, 0x55))
+        .add_output(output(30_000, 0x22))
+        .add_output(output(40_000, 0x22))
+        .build();
+
+    assert_eq!(
+        tx.reused_addresses(Network::Mainnet),
+        vec!["bc1qyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zc6v074".to_string()]
+    );
+
+    let tx = LegacyTransaction::builder()
+        .add_output(output(10_000, 0x22))
+        .add_output(output(20_000, 0x55))
+        .build();
+    assert!(tx.reused_addresses(Network::Mainnet).is_empty());
+}
+
+#[test]
+fn test_propagation_score() {
+    let low = propagation_score(1.0, false, 250);
+    let high = propagation_score(20.0, false, 250);
+    assert!(high > low);
+    assert!(propagation_score(5.0, false, 250) > propagation_score(5.0, false, 90_000));
+
+    for score in [
+        low,
+        high,
+        propagation_score(1e9, false, 0),
+        propagation_score(-1.0, true, 1 << 20),
+    ] {
+        assert!((0.0..=100.0).contains(&score));
+    }
+}
+
+#[test]
+fn test_transaction_base64_round_trip() {
+    let empty = LegacyTransaction::builder().build();
+    assert_eq!(empty.to_base64(), "AQAAAAAAAAAAAA==");
+
+    let tx = LegacyTransaction::builder()
+        .version(2)
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout: 3,
+            },
+            script_sig: vec![0x51],
+            sequence: 0xFFFFFFFD,
+        })
+        .add_output(TxOutput {
+            value: 12_345,
+            script_pubkey: script::p2pkh_script(&[0x22; 20]),
+        })
+        .lock_time(100)
+        .build();
+    let decoded = LegacyTransaction::from_base64(&tx.to_base64()).unwrap();
+    assert_eq!(decoded.to_bytes(), tx.to_bytes());
+
+    assert!(matches!(
+        LegacyTransaction::from_base64("AQAA$AAA"),
+        Err(BitcoinError::ParseError(_))
+    ));
+    assert!(matches!(
+        LegacyTransaction::from_base64("AQA"),
+        Err(BitcoinError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_spends_from_unconfirmed() {
+    let spending = |txid: [u8; 32]| {
+        LegacyTransaction::builder()
+            .add_input(TxInput {
+                previous_output: OutPoint { txid, vout: 0 },
+                script_sig: vec![],
+                sequence: 0xFFFFFFFF,
+            })
+            .build()
+    };
+    let unconfirmed = std::collections::HashSet::from([[0xAA; 32], [0xBB; 32]]);
+
+    assert!(spending([0xBB; 32]).spends_from(&unconfirmed));
+    assert!(!spending([0xCC; 32]).spends_from(&unconfirmed));
+}
+
+#[test]
+fn test_compute_change() {
+    assert_eq!(compute_change(100_000, 60_000, 1_500).unwrap(), 38_500);
+    assert_eq!(compute_change(61_500, 60_000, 1_500).unwrap(), 0);
+
+    assert!(matches!(
+        compute_change(60_000, 60_000, 1),
+        Err(BitcoinError::InvalidAmount)
+    ));
+    assert!(matches!(
+        compute_change(u64::MAX, u64::MAX, 1),
+        Err(BitcoinError::InvalidAmount)
+    ));
+}
+
+#[test]
+fn test_hex_with_offsets() {
+    let mut data = b"Bitcoin\x00\x01\x02 block".to_vec();
+    data.extend([0xff; 10]);
+    let dump = hex::hex_with_offsets(&data);
+    let lines: Vec<&str> = dump.lines().collect();
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0],
+        "00000000  42 69 74 63 6f 69 6e 00 01 02 20 62 6c 6f 63 6b  |Bitcoin... block|"
+    );
+    assert!(lines[1].starts_with("00000010  ff ff ff ff ff ff ff ff ff ff   "));
+    assert!(lines[1].ends_with("  |..........|"));
+}
+
+#[test]
+fn test_decode_flags_non_standard_outputs() {
+    // 1-of-1 bare multisig: OP_1 <33-byte key> OP_1 OP_CHECKMULTISIG
+    let mut multisig = vec![0x51, 33, 0x02];
+    multisig.extend([0x33; 32]);
+    multisig.extend([0x51, 0xae]);
+
+    let tx = LegacyTransaction::builder()
+        .add_output(TxOutput {
+            value: 1_000,
+            script_pubkey: multisig,
+        })
+        .add_output(TxOutput {
+            value: 2_000,
+            script_pubkey: script::p2pkh_script(&[0x22; 20]),
+        })
+        .build();
+    let raw = hex::encode(&tx.to_bytes());
+
+    let report = inspect(&raw, Network::Mainnet).unwrap();
+    assert_eq!(report.outputs[0].script_type, ScriptType::Multisig);
+    assert!(!report.outputs[0].standard);
+    assert!(report.outputs[1].standard);
+
+    let human = decode_output(&raw, DecodeOptions::default()).unwrap();
+    let outputs: Vec<&str> = human.lines().filter(|l| l.starts_with("  #")).collect();
+    assert!(outputs[0].ends_with("[non-standard]"));
+    assert!(!outputs[1].contains("non-standard"));
+}
+
+#[test]
+fn test_confirmations() {
+    assert_eq!(confirmations(850_000, 850_000), 1);
+    assert_eq!(confirmations(800_000, 850_000), 50_001);
+    assert_eq!(confirmations(850_001, 850_000), 0);
+}
+
+#[test]
+fn test_psbt_global_xpubs() {
+    let xpub = decode_xpub(
+        "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+    )
+    .unwrap();
+    let mut key = vec![0x01];
+    key.extend(xpub.to_bytes());
+    let mut origin = vec![0xde, 0xad, 0xbe, 0xef];
+    origin.extend((84 | bip32::HARDENED_INDEX).to_le_bytes());
+    origin.extend(bip32::HARDENED_INDEX.to_le_bytes());
+
+    let mut data = b"psbt\xff".to_vec();
+    push_psbt_record(&mut data, &[0x00], &sample_unsigned_tx().to_bytes());
+    push_psbt_record(&mut data, &key, &origin);
+    data.extend([0x00, 0x00, 0x00]);
+
+    let psbt = Psbt::from_bytes(&data).unwrap();
+    let xpubs = psbt.global_xpubs().unwrap();
+    assert_eq!(xpubs.len(), 1);
+    assert_eq!(xpubs[0].0, xpub);
+    assert_eq!(
+        xpubs[0].1,
+        DerivationPath(vec![84 | bip32::HARDENED_INDEX, bip32::HARDENED_INDEX])
+    );
+}
+
+#[test]
+fn test_psbt_input_derivations() {
+    let mut pubkey = vec![0x02];
+    pubkey.extend([0x44; 32]);
+    let mut key = vec![0x06];
+    key.extend(&pubkey);
+    let mut origin = vec![0xde, 0xad, 0xbe, 0xef];
+    for level in [
+        84 | bip32::HARDENED_INDEX,
+        bip32::HARDENED_INDEX,
+        bip32::HARDENED_INDEX,
+        0,
+        5,
+    ] {
+        origin.extend(level.to_le_bytes());
+    }
+
+    let mut data = b"psbt\xff".to_vec();
+    push_psbt_record(&mut data, &[0x00], &sample_unsigned_tx().to_bytes());
+    data.push(0x00);
+    push_psbt_record(&mut data, &key, &origin);
+    data.extend([0x00, 0x00]);
+
+    let psbt = Psbt::from_bytes(&data).unwrap();
+    let derivations = psbt.input_derivations(0).unwrap();
+    assert_eq!(derivations.len(), 1);
+    let (parsed_key, fingerprint, path) = &derivations[0];
+    assert_eq!(parsed_key, &pubkey);
+    assert_eq!(fingerprint, &[0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(path.0.len(), 5);
+    assert_eq!(path.0[4], 5);
+
+    assert!(matches!(
+        psbt.input_derivations(1),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_derivation_path_from_str() {
+    let hardened = bip32::HARDENED_INDEX;
+    assert_eq!(
+        "m/84'/0'/0'/0/5".parse::<DerivationPath>().unwrap(),
+        DerivationPath(vec![84 | hardened, hardened, hardened, 0, 5])
+    );
+    assert_eq!(
+        "m/44h/1h/2".parse::<DerivationPath>().unwrap(),
+        DerivationPath(vec![44 | hardened, 1 | hardened, 2])
+    );
+    assert_eq!(
+        "m".parse::<DerivationPath>().unwrap(),
+        DerivationPath(vec![])
+    );
+
+    for invalid in [
+        "84'/0'",
+        "m/",
+        "m/x",
+        "m/1//2",
+        "m/-1",
+        "m/2147483648",
+        "m/1''",
+    ] {
+        assert!(
+            matches!(
+                invalid.parse::<DerivationPath>(),
+                Err(BitcoinError::ParseError(_))
+            ),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn test_derivation_path_to_bytes_round_trip() {
+    let path: DerivationPath = "m/48'/0'/0'/2'/1/7".parse().unwrap();
+    let bytes = path.to_bytes();
+
+    assert_eq!(bytes.len(), 24);
+    assert_eq!(bytes[..4], [0x30, 0x00, 0x00, 0x80]);
+    assert_eq!(
+        bytes[16..],
+        [0x01, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00]
+    );
+    assert_eq!(DerivationPath::from_bytes(&bytes).unwrap(), path);
+}
+
+#[test]
+fn test_bip32_fingerprint() {
+    // BIP-32 test vector 1 master public key
+    let pubkey =
+        hex::decode("0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2").unwrap();
+    assert_eq!(bip32::fingerprint(&pubkey), [0x34, 0x42, 0x19, 0x3e]);
+}
+
+#[test]
+fn test_spendable_at() {
+    let input = |sequence: u32| TxInput {
+        previous_output: OutPoint {
+            txid: [0x11; 32],
+            vout: 0,
+        },
+        script_sig: vec![],
+        sequence,
+    };
+
+    // Absolute height lock with a 10-block relative lock on the second input
+    let tx = LegacyTransaction::builder()
+        .version(2)
+        .add_input(input(0xFFFFFFFE))
+        .add_input(input(10))
+        .lock_time(850_000)
+        .build();
+    assert!(!tx.spendable_at(850_000, 0, &[100, 10]));
+    assert!(tx.spendable_at(850_001, 0, &[100, 10]));
+    assert!(!tx.spendable_at(850_001, 0, &[100, 9]));
+    assert!(!tx.spendable_at(850_001, 0, &[100]));
+
+    // Timestamp lock compares against median time past; 2 * 512 seconds ~ 2 blocks
+    let tx = LegacyTransaction::builder()
+        .version(2)
+        .add_input(input(SEQUENCE_LOCKTIME_TYPE_FLAG | 2))
+        .lock_time(1_700_000_000)
+        .build();
+    assert!(!tx.spendable_at(850_000, 1_700_000_000, &[2]));
+    assert!(tx.spendable_at(850_000, 1_700_000_001, &[2]));
+    assert!(!tx.spendable_at(850_000, 1_700_000_001, &[1]));
+
+    // Final sequences disable lock_time
+    let tx = LegacyTransaction::builder()
+        .add_input(input(0xFFFFFFFF))
+        .lock_time(900_000)
+        .build();
+    assert!(tx.spendable_at(850_000, 0, &[0]));
+}
+
+#[test]
+fn test_weight_savings_vs_legacy() {
+    let mut pubkey = vec![0x02];
+    pubkey.extend([0x33; 32]);
+    let tx = SegwitTransaction {
+        version: 2,
+        inputs: vec![TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout: 0,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFD,
+        }],
+        outputs: vec![TxOutput {
+            value: 50_000,
+            script_pubkey: script::witness_script(0, &[0x22; 20]),
+        }],
+        witnesses: vec![vec![vec![0x30; 72], pubkey]],
+        lock_time: 0,
+    };
+
+    // 110 vbytes as P2WPKH against 189 bytes with the signature and key in the scriptSig
+    assert_eq!(tx.vsize(), 110);
+    let savings = weight_savings_vs_legacy(&tx);
+    assert!((savings - (189.0 - 110.0) * 100.0 / 189.0).abs() < 1e-9);
+    assert!(savings > 0.0);
+
+    // A percentage of the same comparison `encoding_efficiency` gives as a fraction
+    let fraction = encoding_efficiency(189, tx.vsize());
+    assert!(fraction < 1.0);
+    assert!((savings - fraction * 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_is_valid_signature_encoding() {
+    // Canonical DER signature with SIGHASH_ALL
+    let sig = hex::decode(
+        "304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee01",
+    )
+    .unwrap();
+    assert!(script::is_valid_signature_encoding(&sig));
+
+    let with = |index: usize, byte: u8| {
+        let mut bad = sig.clone();
+        bad[index] = byte;
+        bad
+    };
+    // Not a sequence, wrong total length, R not an integer
+    assert!(!script::is_valid_signature_encoding(&with(0, 0x31)));
+    assert!(!script::is_valid_signature_encoding(&with(1, 0x45)));
+    assert!(!script::is_valid_signature_encoding(&with(2, 0x03)));
+    // Negative R, undefined sighash type
+    assert!(!script::is_valid_signature_encoding(&with(4, 0x80)));
+    assert!(!script::is_valid_signature_encoding(&with(
+        sig.len() - 1,
+        0x04
+    )));
+    assert!(script::is_valid_signature_encoding(&with(
+        sig.len() - 1,
+        0x83
+    )));
+
+    // Unnecessary leading zero on R
+    let mut padded = vec![0x30, 0x45, 0x02, 0x21, 0x00];
+    padded.extend(&sig[4..]);
+    assert!(!script::is_valid_signature_encoding(&padded));
+
+    assert!(!script::is_valid_signature_encoding(&sig[..8]));
+    assert!(!script::is_valid_signature_encoding(&[]));
+}
+
+#[test]
+fn test_script_type_set() {
+    let output = |script_pubkey: Vec<u8>| TxOutput {
+        value: 1_000,
+        script_pubkey,
+    };
+    let tx = LegacyTransaction::builder()
+        .add_output(output(script::p2pkh_script(&[0x11; 20])))
+        .add_output(output(script::witness_script(0, &[0x22; 20])))
+        .add_output(output(script::witness_script(0, &[0x33; 20])))
+        .add_output(output(script::witness_script(1, &[0x44; 32])))
+        .build();
+
+    assert_eq!(
+        tx.script_type_set(),
+        std::collections::HashSet::from([ScriptType::P2PKH, ScriptType::P2WPKH, ScriptType::P2TR])
+    );
+    assert!(
+        LegacyTransaction::builder()
+            .build()
+            .script_type_set()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_decode_from_file() {
+    let tx = sample_unsigned_tx();
+    let dir = std::env::temp_dir();
+    let raw_path = dir.join(format!("decode-test-{}.bin", std::process::id()));
+    let hex_path = dir.join(format!("decode-test-{}.hex", std::process::id()));
+    std::fs::write(&raw_path, tx.to_bytes()).unwrap();
+    std::fs::write(&hex_path, format!("{}\n", hex::encode(&tx.to_bytes()))).unwrap();
+
+    let expected_txid = hex::encode_reversed(&tx.txid());
+    let from_raw = decode_file_output(
+        &raw_path,
+        DecodeOptions {
+            csv: true,
+            strict: false,
+        },
+    )
+    .unwrap();
+    let from_hex = decode_file_output(
+        &hex_path,
+        DecodeOptions {
+            csv: true,
+            strict: false,
+        },
+    )
+    .unwrap();
+    std::fs::remove_file(&raw_path).unwrap();
+    std::fs::remove_file(&hex_path).unwrap();
+
+    assert!(from_raw.lines().nth(1).unwrap().starts_with(&expected_txid));
+    assert_eq!(from_raw, from_hex);
+
+    let args = vec![
+        "decode".to_string(),
+        "--file".to_string(),
+        raw_path.display().to_string(),
+    ];
+    assert!(matches!(parse_cli_args(&args), Err(BitcoinError::Io(_))));
+}
+
+#[test]
+fn test_effective_value() {
+    // A P2WPKH input is 68 vbytes: 680 sat at 10 sat/vB
+    assert_eq!(effective_value(100_000, ScriptType::P2WPKH, 10.0), 99_320);
+    assert_eq!(effective_value(546, ScriptType::P2PKH, 10.0), 546 - 1_480);
+
+    // Uneconomical UTXOs are left out of selection
+    let utxo = |value: u64, vout: u32| {
+        (
+            OutPoint {
+                txid: [0x11; 32],
+                vout,
+            },
+            TxOutput {
+                value,
+                script_pubkey: script::p2pkh_script(&[0x22; 20]),
+            },
+        )
+    };
+    let recipients = vec![(
+        "bc1qyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zc6v074".to_string(),
+        10_000,
+    )];
+    let result = estimate_batch_payout(
+        &recipients,
+        &[utxo(1_000, 0)],
+        10.0,
+        ScriptType::P2PKH,
+        Network::Mainnet,
+    );
+    assert!(matches!(result, Err(BitcoinError::InvalidAmount)));
+
+    let estimate = estimate_batch_payout(
+        &recipients,
+        &[utxo(1_000, 0), utxo(50_000, 1)],
+        10.0,
+        ScriptType::P2PKH,
+        Network::Mainnet,
+    )
+    .unwrap();
+    assert_eq!(estimate.inputs_used, 1);
+}
+
+#[test]
+fn test_select_coins_bnb() {
+    let outpoint = |vout: u32| OutPoint {
+        txid: [0x11; 32],
+        vout,
+    };
+    // At 1 sat/vB each P2WPKH input costs 68 sat, so these are worth
+    // 12,000 / 20,000 / 30,000 / 5,000 after fees
+    let utxos = vec![
+        (outpoint(0), 12_068),
+        (outpoint(1), 20_068),
+        (outpoint(2), 30_068),
+        (outpoint(3), 5_068),
+    ];
+
+    let mut selected = select_coins_bnb(&utxos, 35_000, 1.0).unwrap();
+    selected.sort_by_key(|outpoint| outpoint.vout);
+    assert_eq!(selected, vec![outpoint(2), outpoint(3)]);
+
+    // Within the 99 sat cost-of-change window
+    let selected = select_coins_bnb(&utxos, 29_950, 1.0).unwrap();
+    assert_eq!(selected, vec![outpoint(2)]);
+
+    // Every combination overshoots by more than the cost of change
+    assert_eq!(select_coins_bnb(&utxos, 1_000, 1.0), None);
+    assert_eq!(select_coins_bnb(&utxos, 70_000, 1.0), None);
+}
+
+#[test]
+fn test_selection_waste() {
+    // Inputs cost 680 sat at 10 sat/vB and 340 sat at the 5 sat/vB long-term rate
+    let single = selection_waste(&[50_680], 50_000, 10.0, 5.0);
+    let pair = selection_waste(&[30_680, 20_680], 50_000, 10.0, 5.0);
+    assert_eq!(single, 340);
+    assert_eq!(pair, 2 * 340);
+    assert!(single < pair);
+
+    // Below the long-term rate spending more inputs now is rewarded
+    assert_eq!(selection_waste(&[30_340, 20_340], 50_000, 5.0, 10.0), -680);
+}
+
+#[test]
+fn test_split_payout() {
+    let recipients: Vec<(String, u64)> = (0..25)
+        .map(|i| (format!("address-{i}"), 10_000 + i))
+        .collect();
+    let batches = split_payout(&recipients, 10);
+
+    assert_eq!(
+        batches.iter().map(Vec::len).collect::<Vec<_>>(),
+        vec![10, 10, 5]
+    );
+    assert_eq!(batches[2][4], ("address-24".to_string(), 10_024));
+    assert_eq!(batches.concat(), recipients);
+}
+
+#[test]
+fn test_merkle_proof_four_transactions() {
+    let txids = [[0x01; 32], [0x02; 32], [0x03; 32], [0x04; 32]];
+    let root = merkle::merkle_root(&txids).unwrap();
+    assert_eq!(
+        hex::encode(&root),
+        "085aabaef98668701b87c9a1986bdf116726a9949802326b69895697d4e8c812"
+    );
+
+    for (index, txid) in txids.iter().enumerate() {
+        let proof = merkle::merkle_proof(&txids, index).unwrap();
+        assert_eq!(proof.len(), 2);
+        assert!(merkle::verify_merkle_proof(txid, &proof, &root));
+    }
+
+    let proof = merkle::merkle_proof(&txids, 2).unwrap();
+    assert_eq!(proof[0], ([0x04; 32], true));
+    assert!(!proof[1].1);
+    assert!(!merkle::verify_merkle_proof(&[0x05; 32], &proof, &root));
+    assert!(!merkle::verify_merkle_proof(&txids[1], &proof, &root));
+
+    assert!(matches!(
+        merkle::merkle_proof(&txids, 4),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_dust_outputs() {
+    let output = |value: u64| TxOutput {
+        value,
+        script_pubkey: script::p2pkh_script(&[0x22; 20]),
+    };
+    let tx = LegacyTransaction::builder()
+        .add_output(output(50_000))
+        .add_output(output(546))
+        .build();
+
+    assert_eq!(tx.dust_outputs(546), vec![1]);
+    assert!(tx.dust_outputs(545).is_empty());
+}
+
+#[test]
+fn test_signing_requests() {
+    let input = |txid: [u8; 32], vout: u32| TxInput {
+        previous_output: OutPoint { txid, vout },
+        script_sig: vec![],
+        sequence: 0xFFFFFFFD,
+    };
+    let tx = LegacyTransaction::builder()
+        .version(2)
+        .add_input(input([0x11; 32], 0))
+        .add_input(input([0x44; 32], 1))
+        .add_output(TxOutput {
+            value: 40_000,
+            script_pubkey: script::p2pkh_script(&[0x55; 20]),
+        })
+        .build();
+    let prevout_scripts = vec![
+        script::p2pkh_script(&[0x22; 20]),
+        script::p2pkh_script(&[0x33; 20]),
+    ];
+
+    let sighashes = tx
+        .signing_requests(&prevout_scripts, script::SIGHASH_ALL as u32)
+        .unwrap();
+    assert_eq!(sighashes.len(), 2);
+    assert_eq!(
+        hex::encode(&sighashes[0]),
+        "f4e065fd1c48cc6f1cbf1202d4d47477f7303314168cdc88e65e3a020abb94c7"
+    );
+    assert_eq!(
+        hex::encode(&sighashes[1]),
+        "446c8e34496017ec59ef365f4f19895eee6481a39156d0baa9479238caaa2aaa"
+    );
+
+    // SIGHASH_SINGLE for an input without a matching output signs the number one
+    let single = tx
+        .signing_requests(&prevout_scripts, script::SIGHASH_SINGLE as u32)
+        .unwrap();
+    assert_ne!(single[0], sighashes[0]);
+    assert_eq!(single[1][0], 1);
+    assert!(single[1][1..].iter().all(|&byte| byte == 0));
+
+    assert!(matches!(
+        tx.signing_requests(&prevout_scripts[..1], script::SIGHASH_ALL as u32),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_apply_signatures() {
+    let mut tx = sample_unsigned_tx();
+    let signature = vec![0x30; 71];
+    let mut pubkey = vec![0x02];
+    pubkey.extend([0x33; 32]);
+
+    tx.apply_signatures(&[(0, signature.clone(), pubkey.clone())])
+        .unwrap();
+    let mut expected = vec![71];
+    expected.extend(&signature);
+    expected.push(33);
+    expected.extend(&pubkey);
+    assert_eq!(tx.inputs[0].script_sig, expected);
+
+    let mut tx = sample_unsigned_tx();
+    assert!(matches!(
+        tx.apply_signatures(&[
+            (0, signature.clone(), pubkey.clone()),
+            (1, signature, pubkey)
+        ]),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+    assert!(tx.inputs[0].script_sig.is_empty());
+}
+
+#[test]
+fn test_min_economical_change() {
+    // Dust limits: (31 + 67) * 3 for P2WPKH and (34 + 148) * 3 for P2PKH
+    assert_eq!(min_economical_change(ScriptType::P2WPKH, 1.0), 294);
+    assert_eq!(min_economical_change(ScriptType::P2PKH, 1.0), 546);
+
+    // At higher rates the cost to spend dominates: 68 or 148 vbytes
+    assert_eq!(min_economical_change(ScriptType::P2WPKH, 20.0), 1_361);
+    assert_eq!(min_economical_change(ScriptType::P2PKH, 20.0), 2_961);
+}
+
+#[test]
+fn test_same_as() {
+    let tx = sample_unsigned_tx();
+    let reparsed = LegacyTransaction::from_bytes(&tx.to_bytes()).unwrap();
+    assert!(tx.same_as(&reparsed));
+    assert!(tx.same_as(&sample_unsigned_tx()));
+
+    let mut bumped = sample_unsigned_tx();
+    bumped.outputs[0].value -= 1_000;
+    assert!(!tx.same_as(&bumped));
+}
+
+#[test]
+fn test_build_p2pkh_script_sig() {
+    let signature = hex::decode(
+        "304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee01",
+    )
+    .unwrap();
+    let pubkey =
+        hex::decode("0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2").unwrap();
+
+    let script_sig = script::build_p2pkh_script_sig(&signature, &pubkey);
+    assert_eq!(script_sig.len(), 1 + 71 + 1 + 33);
+    assert_eq!(script_sig[0], 0x47);
+    assert_eq!(script_sig[1..72], signature[..]);
+    assert_eq!(script_sig[72], 0x21);
+    assert_eq!(script_sig[73..], pubkey[..]);
+    assert_eq!(
+        script::parse_pushes(&script_sig).unwrap(),
+        vec![signature, pubkey]
+    );
+}
+
+#[test]
+fn test_declared_counts_do_not_over_allocate() {
+    // Simplified format: 4 billion inputs and outputs declared in 16 bytes
+    let mut data = vec![0x02, 0x00, 0x00, 0x00];
+    data.extend([0xff; 8]);
+    data.extend([0x00; 4]);
+    let tx = LegacyTransaction::try_from(data.as_slice()).unwrap();
+    assert!(tx.inputs.capacity() <= MAX_PREALLOCATED_ITEMS);
+    assert!(tx.outputs.capacity() <= MAX_PREALLOCATED_ITEMS);
+
+    // Consensus format: a 2^64 - 1 input count followed by a few bytes
+    let mut data = vec![0x02, 0x00, 0x00, 0x00, 0xff];
+    data.extend([0xff; 8]);
+    data.extend([0x00; 8]);
+    assert!(matches!(
+        LegacyTransaction::from_bytes(&data),
+        Err(BitcoinError::ParseError(_))
+    ));
+
+    // Counts above the cap still parse once the items are really there
+    let mut builder = LegacyTransaction::builder();
+    for _ in 0..MAX_PREALLOCATED_ITEMS + 5 {
+        builder = builder.add_output(TxOutput {
+            value: 1,
+            script_pubkey: vec![],
+        });
+    }
+    let tx = builder.build();
+    let parsed = LegacyTransaction::from_bytes(&tx.to_bytes()).unwrap();
+    assert_eq!(parsed.outputs.len(), MAX_PREALLOCATED_ITEMS + 5);
+}
+
+#[test]
+fn test_transaction_to_psbt() {
+    let tx = sample_unsigned_tx();
+    let prevout = TxOutput {
+        value: 100_000,
+        script_pubkey: script::witness_script(0, &[0x33; 20]),
+    };
+
+    let psbt = tx.to_psbt(std::slice::from_ref(&prevout)).unwrap();
+    let reparsed = Psbt::from_bytes(&psbt.serialize()).unwrap();
+    assert_eq!(reparsed.unsigned_tx.to_bytes(), tx.to_bytes());
+    assert_eq!(reparsed.outputs.len(), 1);
+
+    let utxo = reparsed.witness_utxo(0).unwrap().unwrap();
+    assert_eq!(utxo.value, prevout.value);
+    assert_eq!(utxo.script_pubkey, prevout.script_pubkey);
+
+    assert!(matches!(
+        tx.to_psbt(&[]),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_bip125_replacement_requirements() {
+    // Rules 3 and 4: original fee plus 1 sat/vB for the 200 vbyte replacement
+    assert_eq!(bip125_replacement_requirements(1_000, 200, 200, 1.0), 1_200);
+    // A smaller replacement still has to beat the original absolute fee
+    assert_eq!(bip125_replacement_requirements(5_000, 500, 150, 1.0), 5_150);
+    // A much larger replacement has to beat the original fee rate of 50 sat/vB
+    assert_eq!(
+        bip125_replacement_requirements(5_000, 100, 400, 1.0),
+        20_001
+    );
+}
+
+#[test]
+fn test_address_from_str() {
+    let genesis: Address = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap();
+    assert_eq!(*genesis.network(), Network::Mainnet);
+    assert_eq!(genesis.script_type(), ScriptType::P2PKH);
+    assert_eq!(
+        hex::encode(genesis.payload()),
+        "62e907b15cbf27d5425399ebf6f0fb50ebb88f18"
+    );
+    assert_eq!(genesis.to_script_pubkey()[..3], [0x76, 0xa9, 0x14]);
+
+    let p2sh: Address = "39UDauvY7xog2eQo8a7NFfnXh2NhXV6iH1".parse().unwrap();
+    assert_eq!(p2sh.script_type(), ScriptType::P2SH);
+    assert_eq!(p2sh.to_script_pubkey(), script::p2sh_script(&[0x55; 20]));
+
+    let p2wpkh: Address = "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4"
+        .parse()
+        .unwrap();
+    assert_eq!(*p2wpkh.network(), Network::Mainnet);
+    assert_eq!(p2wpkh.script_type(), ScriptType::P2WPKH);
+    assert_eq!(
+        hex::encode(&p2wpkh.to_script_pubkey()),
+        "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+    );
+
+    let p2tr: Address = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+        .parse()
+        .unwrap();
+    assert_eq!(p2tr.script_type(), ScriptType::P2TR);
+    assert_eq!(
+        hex::encode(p2tr.payload()),
+        "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+    );
+    assert_eq!(
+        p2tr.to_string(),
+        "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+    );
+
+    let testnet: Address = "mprPtVohJ6d1JgjT8LWJcy83KCJV1PgxDS".parse().unwrap();
+    assert_eq!(*testnet.network(), Network::Testnet);
+
+    assert!(matches!(
+        "not an address".parse::<Address>(),
+        Err(BitcoinError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_address_new_validates_payload() {
+    let address = Address::new(Network::Mainnet, ScriptType::P2PKH, vec![0x55; 20]).unwrap();
+    assert_eq!(
+        address.to_script_pubkey(),
+        script::p2pkh_script(&[0x55; 20])
+    );
+
+    for (script_type, len) in [
+        (ScriptType::P2PKH, 32),
+        (ScriptType::P2SH, 19),
+        (ScriptType::P2WPKH, 32),
+        (ScriptType::P2TR, 20),
+        (ScriptType::Multisig, 20),
+    ] {
+        assert!(matches!(
+            Address::new(Network::Mainnet, script_type, vec![0; len]),
+            Err(BitcoinError::ParseError(_))
+        ));
+    }
+}
+
+#[test]
+fn test_witness_weight_fraction() {
+    // Large witness script, as when spending a big multisig P2WSH output
+    let tx = SegwitTransaction {
+        version: 2,
+        inputs: vec![TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout: 0,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFF,
+        }],
+        outputs: vec![TxOutput {
+            value: 50_000,
+            script_pubkey: script::witness_script(0, &[0x22; 20]),
+        }],
+        witnesses: vec![vec![
+            vec![],
+            vec![0x30; 72],
+            vec![0x30; 72],
+            vec![0x52; 300],
+        ]],
+        lock_time: 0,
+    };
+
+    // 82 base bytes; 2 + 1 + 1 + 73 + 73 + 3 + 300 = 453 witness bytes
+    assert_eq!(tx.base_size(), 82);
+    assert_eq!(tx.total_size(), 82 + 453);
+    let fraction = tx.witness_weight_fraction();
+    assert!((fraction - 453.0 / (82.0 * 4.0 + 453.0)).abs() < 1e-12);
+    assert!(fraction > 0.5);
+}
+
+#[test]
+fn test_estimate_confirmation_blocks() {
+    let buckets = [(20.0, 1), (5.0, 6), (1.0, 144)];
+
+    assert_eq!(estimate_confirmation_blocks(35.0, &buckets), 1);
+    assert_eq!(estimate_confirmation_blocks(20.0, &buckets), 1);
+    assert_eq!(estimate_confirmation_blocks(12.5, &buckets), 6);
+    assert_eq!(estimate_confirmation_blocks(1.0, &buckets), 144);
+    assert_eq!(estimate_confirmation_blocks(0.5, &buckets), u32::MAX);
+    assert_eq!(estimate_confirmation_blocks(10.0, &[]), u32::MAX);
+}
+
+#[test]
+fn test_from_bytes_consumed() {
+    let first = sample_unsigned_tx();
+    let second = LegacyTransaction::builder().lock_time(7).build();
+    let mut stream = first.to_bytes();
+    stream.extend(second.to_bytes());
+
+    let (parsed, consumed) = LegacyTransaction::from_bytes_consumed(&stream).unwrap();
+    assert_eq!(consumed, first.to_bytes().len());
+    assert!(parsed.same_as(&first));
+
+    let (parsed, consumed) = LegacyTransaction::from_bytes_consumed(&stream[consumed..]).unwrap();
+    assert_eq!(consumed, second.to_bytes().len());
+    assert_eq!(parsed.lock_time, 7);
+
+    // from_bytes still rejects the trailing transaction
+    assert!(matches!(
+        LegacyTransaction::from_bytes(&stream),
+        Err(BitcoinError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_check_io_limits() {
+    let input = TxInput {
+        previous_output: OutPoint {
+            txid: [0x11; 32],
+            vout: 0,
+        },
+        script_sig: vec![],
+        sequence: 0xFFFFFFFF,
+    };
+    // 8 + 3 + 1 + 41 * 24,389 bytes is just under the 1,000,000 byte limit
+    let mut tx = LegacyTransaction::builder().build();
+    tx.inputs = vec![input.clone(); 24_389];
+    assert!(tx.check_io_limits().is_ok());
+
+    tx.inputs.push(input);
+    assert!(matches!(
+        tx.check_io_limits(),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+    assert!(sample_unsigned_tx().check_io_limits().is_ok());
+}
+
+#[test]
+fn test_unsigned_txid() {
+    let unsigned = sample_unsigned_tx();
+    let mut signed = sample_unsigned_tx();
+    signed.inputs[0].script_sig = script::build_p2pkh_script_sig(&[0x30; 71], &[0x02; 33]);
+
+    assert_ne!(signed.txid(), unsigned.txid());
+    assert_eq!(signed.unsigned_txid(), unsigned.unsigned_txid());
+    assert_eq!(unsigned.unsigned_txid(), unsigned.txid());
+
+    let mut changed = sample_unsigned_tx();
+    changed.outputs[0].value += 1;
+    assert_ne!(changed.unsigned_txid(), unsigned.unsigned_txid());
+}
+
+#[test]
+fn test_referenced_pubkeys() {
+    let mut input_key = vec![0x03];
+    input_key.extend([0x44; 32]);
+    let mut p2pk_key = vec![0x04];
+    p2pk_key.extend([0x55; 64]);
+    let mut p2pk = vec![65];
+    p2pk.extend(&p2pk_key);
+    p2pk.push(0xac);
+
+    let mut tx = sample_unsigned_tx();
+    tx.inputs[0].script_sig = script::build_p2pkh_script_sig(&[0x30; 71], &input_key);
+    tx.outputs.push(TxOutput {
+        value: 1_000,
+        script_pubkey: p2pk,
+    });
+
+    assert_eq!(tx.referenced_pubkeys(), vec![input_key, p2pk_key]);
+    assert!(sample_unsigned_tx().referenced_pubkeys().is_empty());
+}
+
+#[test]
+fn test_format_btc_rounding() {
+    let amount = 0.123456785;
+    assert_eq!(format_btc(amount, RoundingMode::Truncate), "0.12345678 BTC");
+    assert_eq!(format_btc(amount, RoundingMode::HalfUp), "0.12345679 BTC");
+    assert_eq!(
+        format_btc(0.123456781, RoundingMode::HalfUp),
+        "0.12345678 BTC"
+    );
+    assert_eq!(
+        format_btc(0.123456781, RoundingMode::Ceil),
+        "0.12345679 BTC"
+    );
+
+    // Exact satoshi amounts are unaffected by the mode
+    for mode in [
+        RoundingMode::Truncate,
+        RoundingMode::HalfUp,
+        RoundingMode::Ceil,
+    ] {
+        assert_eq!(format_btc(0.1, mode), "0.10000000 BTC");
+        assert_eq!(format_btc(21.0, mode), "21.00000000 BTC");
+    }
+}
+
+#[test]
+fn test_canonical_json_snapshot() {
+    let expected = concat!(
+        r#"{"inputs":[{"script_sig":"","sequence":4294967293,"#,
+        r#""txid":"1111111111111111111111111111111111111111111111111111111111111111","vout":0}],"#,
+        r#""lock_time":0,"#,
+        r#""outputs":[{"script_pubkey":"00142222222222222222222222222222222222222222","value":90000}],"#,
+        r#""txid":"8daedf4374b780a9a425ce1b574ee5625a692b4c7067835f1ead837fe7d8480d","#,
+        r#""version":2}"#
+    );
+    assert_eq!(sample_unsigned_tx().canonical_json(), expected);
+}
+
+#[test]
+fn test_input_output_weight() {
+    // 32 + 4 + 1 + 107 + 4 bytes with a 72 byte signature and compressed key
+    let input = TxInput {
+        previous_output: OutPoint {
+            txid: [0x11; 32],
+            vout: 0,
+        },
+        script_sig: script::build_p2pkh_script_sig(&[0x30; 72], &[0x02; 33]),
+        sequence: 0xFFFFFFFF,
+    };
+    assert_eq!(input.weight(), 148 * 4);
+
+    // 8 + 1 + 25 bytes
+    let output = TxOutput {
+        value: 10_000,
+        script_pubkey: script::p2pkh_script(&[0x22; 20]),
+    };
+    assert_eq!(output.weight(), 34 * 4);
+
+    let tx = LegacyTransaction::builder()
+        .add_input(input.clone())
+        .add_output(output.clone())
+        .build();
+    assert_eq!(tx.weight(), 10 * 4 + input.weight() + output.weight());
+}
+
+#[test]
+fn test_decode_strict_flags_non_push_script_sig() {
+    let mut tx = sample_unsigned_tx();
+    tx.inputs[0].script_sig = vec![0x76, 0x51];
+    tx.inputs.push(TxInput {
+        script_sig: vec![0x51],
+        ..tx.inputs[0].clone()
+    });
+    let report = inspect(&hex::encode(&tx.to_bytes()), Network::Mainnet).unwrap();
+    assert!(!report.inputs[0].push_only);
+    assert!(report.inputs[1].push_only);
+
+    let strict = render_report(
+        &report,
+        DecodeOptions {
+            csv: false,
+            strict: true,
+        },
+    );
+    let inputs: Vec<&str> = strict
+        .lines()
+        .filter(|l| l.contains(" sequence "))
+        .collect();
+    assert!(inputs[0].ends_with("[non-standard scriptSig]"));
+    assert!(!inputs[1].contains("non-standard"));
+
+    let relaxed = render_report(&report, DecodeOptions::default());
+    assert!(!relaxed.contains("non-standard scriptSig"));
+}
+
+#[test]
+fn test_decode_csv_conflicts_with_strict() {
+    let raw = hex::encode(&sample_unsigned_tx().to_bytes());
+    let args: Vec<String> = ["decode", &raw, "--csv", "--strict"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    assert!(matches!(
+        parse_cli_args(&args),
+        Err(BitcoinError::ParseError(_))
+    ));
+
+    let both = DecodeOptions {
+        csv: true,
+        strict: true,
+    };
+    assert!(matches!(
+        decode_output(&raw, both),
+        Err(BitcoinError::ParseError(_))
+    ));
+
+    // The helpers now honour --strict instead of ignoring it
+    let mut tx = sample_unsigned_tx();
+    tx.inputs[0].script_sig = vec![0x76, 0x51];
+    let strict = DecodeOptions {
+        csv: false,
+        strict: true,
+    };
+    let report = decode_output(&hex::encode(&tx.to_bytes()), strict).unwrap();
+    assert!(report.contains("[non-standard scriptSig]"));
+}
+
+#[test]
+fn test_weight_unit_conversions() {
+    assert_eq!(wu_to_vbytes(3), 1);
+    assert_eq!(wu_to_vbytes(4), 1);
+    assert_eq!(wu_to_vbytes(5), 2);
+    assert_eq!(wu_to_vbytes(0), 0);
+
+    assert_eq!(vbytes_to_wu(1), 4);
+    assert_eq!(wu_to_vbytes(vbytes_to_wu(110)), 110);
+}
+
+#[test]
+fn test_looks_like_coinjoin() {
+    let input = |vout: u32| TxInput {
+        previous_output: OutPoint {
+            txid: [0x11; 32],
+            vout,
+        },
+        script_sig: vec![],
+        sequence: 0xFFFFFFFF,
+    };
+    let output = |value: u64, hash: u8| TxOutput {
+        value,
+        script_pubkey: script::witness_script(0, &[hash; 20]),
+    };
+
+    let mut builder = LegacyTransaction::builder();
+    for i in 0..5 {
+        builder = builder
+            .add_input(input(i))
+            .add_output(output(1_000_000, i as u8))
+            .add_output(output(12_345 + i as u64, 0x80 + i as u8));
+    }
+    let tx = builder.build();
+    assert!(tx.looks_like_coinjoin());
+    assert!(!tx.looks_like_coinjoin_with_threshold(6));
+
+    // Equal outputs funded by a single input are a batch payout, not a coinjoin
+    let mut builder = LegacyTransaction::builder().add_input(input(0));
+    for i in 0..5 {
+        builder = builder.add_output(output(1_000_000, i));
+    }
+    assert!(!builder.build().looks_like_coinjoin());
+}
+
+#[test]
+fn test_feerate_to_evict() {
+    assert_eq!(feerate_to_evict(2.5, 1.0), 3.5);
+    assert!(feerate_to_evict(2.5, 0.1) > 2.5);
+    // A negative margin never recommends less than the minimum
+    assert_eq!(feerate_to_evict(2.5, -1.0), 2.5);
+}
+
+#[test]
+fn test_build_spend_with_fee() {
+    let utxo = |value: u64, vout: u32| {
+        (
+            OutPoint {
+                txid: [0x11; 32],
+                vout,
+            },
+            TxOutput {
+                value,
+                script_pubkey: script::witness_script(0, &[0x22; 20]),
+            },
+        )
+    };
+    let utxos = vec![utxo(30_000, 0), utxo(50_000, 1), utxo(10_000, 2)];
+    let recipient = script::witness_script(0, &[0x33; 20]);
+    let change = script::witness_script(0, &[0x44; 20]);
+
+    let tx = build_spend_with_fee(&utxos, 60_000, &recipient, &change, 1_500).unwrap();
+    assert_eq!(tx.inputs.len(), 2);
+    assert_eq!(tx.inputs[0].previous_output.vout, 1);
+    assert_eq!(tx.outputs[0].value, 60_000);
+    assert_eq!(tx.outputs[0].script_pubkey, recipient);
+    assert_eq!(tx.outputs[1].value, 80_000 - 60_000 - 1_500);
+    assert_eq!(tx.outputs[1].script_pubkey, change);
+
+    // Exactly covered: no change output
+    let tx = build_spend_with_fee(&utxos, 88_500, &recipient, &change, 1_500).unwrap();
+    assert_eq!(tx.outputs.len(), 1);
+
+    assert!(matches!(
+        build_spend_with_fee(&utxos, 89_000, &recipient, &change, 1_500),
+        Err(BitcoinError::InvalidAmount)
+    ));
+
+    // 100 sat of change would be dust and silently raise the fee
+    assert!(matches!(
+        build_spend_with_fee(&utxos, 88_400, &recipient, &change, 1_500),
+        Err(BitcoinError::InvalidAmount)
+    ));
+
+    // Selecting the second UTXO would overflow the running total
+    let huge = vec![utxo(u64::MAX - 1, 0), utxo(2, 1)];
+    assert!(matches!(
+        build_spend_with_fee(&huge, u64::MAX - 1_500, &recipient, &change, 1_500),
+        Err(BitcoinError::InvalidAmount)
+    ));
+}
+
+#[test]
+fn test_sub_transaction_count() {
+    let input = |vout: u32| TxInput {
+        previous_output: OutPoint {
+            txid: [0x11; 32],
+            vout,
+        },
+        script_sig: vec![],
+        sequence: 0xFFFFFFFF,
+    };
+    let output = |value: u64| TxOutput {
+        value,
+        script_pubkey: script::witness_script(0, &[0x22; 20]),
+    };
+
+    // Both inputs together, or either input paying either output
+    let mix = LegacyTransaction::builder()
+        .add_input(input(0))
+        .add_input(input(1))
+        .add_output(output(99_000))
+        .add_output(output(99_000))
+        .build();
+    assert_eq!(mix.sub_transaction_count(&[100_000, 100_000]).unwrap(), 3);
+
+    // Neither input alone covers the large output
+    let payment = LegacyTransaction::builder()
+        .add_input(input(0))
+        .add_input(input(1))
+        .add_output(output(150_000))
+        .add_output(output(49_000))
+        .build();
+    assert_eq!(
+        payment.sub_transaction_count(&[100_000, 100_000]).unwrap(),
+        1
+    );
+
+    assert!(matches!(
+        mix.sub_transaction_count(&[100_000]),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+    assert!(matches!(
+        mix.sub_transaction_count(&[1_000, 1_000]),
+        Err(BitcoinError::InvalidAmount)
+    ));
+    // Input amounts summing past u64::MAX are rejected rather than overflowing
+    assert!(matches!(
+        mix.sub_transaction_count(&[u64::MAX, 1]),
+        Err(BitcoinError::InvalidAmount)
+    ));
+}
+
+#[test]
+fn test_create_replacement() {
+    let tx = LegacyTransaction::builder()
+        .version(2)
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout: 0,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFD,
+        })
+        .add_output(TxOutput {
+            value: 5_000_000,
+            script_pubkey: script::witness_script(0, &[0x44; 20]),
+        })
+        .add_output(TxOutput {
+            value: 1_234_567,
+            script_pubkey: script::p2pkh_script(&[0x55; 20]),
+        })
+        .build();
+    let input_values = [6_235_567];
+    let vsize = tx.vsize() as u64;
+
+    // Original pays 1,000 sat; at 20 sat/vB the change covers the difference
+    let bumped = tx.create_replacement(20.0, &input_values).unwrap();
+    assert_eq!(bumped.inputs.len(), 1);
+    assert_eq!(bumped.outputs[0].value, 5_000_000);
+    assert_eq!(bumped.outputs[1].value, 1_234_567 - (20 * vsize - 1_000));
+
+    // A rate below the original still meets the BIP-125 minimum
+    let minimal = tx.create_replacement(0.5, &input_values).unwrap();
+    assert_eq!(minimal.outputs[1].value, 1_234_567 - vsize);
+
+    // Change cannot absorb the bump
+    assert!(matches!(
+        tx.create_replacement(20_000.0, &input_values),
+        Err(BitcoinError::InvalidAmount)
+    ));
+}
+
+#[test]
+fn test_is_provably_unspendable() {
+    let output = |script_pubkey: Vec<u8>| TxOutput {
+        value: 0,
+        script_pubkey,
+    };
+
+    assert!(output(script::op_return_script(b"burn").unwrap()).is_provably_unspendable());
+    assert!(output(vec![0x50, 0x51]).is_provably_unspendable());
+    assert!(output(vec![0x51; 10_001]).is_provably_unspendable());
+
+    assert!(!output(script::p2pkh_script(&[0x55; 20])).is_provably_unspendable());
+    assert!(!output(vec![]).is_provably_unspendable());
+}
+
+#[test]
+fn test_parse_outputs_only() {
+    let mut tx = sample_unsigned_tx();
+    tx.inputs[0].script_sig = vec![0x51; 300];
+    tx.outputs.push(TxOutput {
+        value: 1_234,
+        script_pubkey: script::p2pkh_script(&[0x55; 20]),
+    });
+    let bytes = tx.to_bytes();
+
+    let outputs = LegacyTransaction::parse_outputs_only(&bytes).unwrap();
+    let full = LegacyTransaction::from_bytes(&bytes).unwrap();
+    assert_eq!(outputs.len(), full.outputs.len());
+    for (lazy, parsed) in outputs.iter().zip(&full.outputs) {
+        assert_eq!(lazy.value, parsed.value);
+        assert_eq!(lazy.script_pubkey, parsed.script_pubkey);
+    }
+
+    assert!(LegacyTransaction::parse_outputs_only(&bytes[..bytes.len() - 1]).is_err());
+    assert!(LegacyTransaction::parse_outputs_only(&[bytes.clone(), vec![0]].concat()).is_err());
+}
+
+fn sample_block(transactions: Vec<LegacyTransaction>) -> Block {
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash: [0; 32],
+            merkle_root: [0; 32],
+            time: 1_231_006_505,
+            bits: block::GENESIS_BITS,
+            nonce: 0,
+        },
+        transactions,
+    }
+}
+
+#[test]
+fn test_output_type_histogram() {
+    let payment = LegacyTransaction::builder()
+        .add_output(TxOutput {
+            value: 10_000,
+            script_pubkey: script::witness_script(0, &[0x44; 20]),
+        })
+        .add_output(TxOutput {
+            value: 20_000,
+            script_pubkey: script::p2pkh_script(&[0x55; 20]),
+        })
+        .add_output(TxOutput {
+            value: 0,
+            script_pubkey: script::op_return_script(b"hi").unwrap(),
+        })
+        .build();
+    let block = sample_block(vec![coinbase_tx(&[625_000_000, 1_000]), payment]);
+
+    let histogram = block.output_type_histogram();
+    assert_eq!(histogram.len(), 3);
+    assert_eq!(histogram[&ScriptType::P2PKH], 3);
+    assert_eq!(histogram[&ScriptType::P2WPKH], 1);
+    assert_eq!(histogram[&ScriptType::OpReturn], 1);
+}
+
+#[test]
+fn test_can_replace() {
+    assert!(can_replace(false, true).is_ok());
+    assert!(matches!(
+        can_replace(true, true),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+    assert!(matches!(
+        can_replace(false, false),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_total_spend_cost() {
+    // Change adds an output now and an input later
+    let spend_later = fee_for_vbytes(estimated_input_vbytes(ScriptType::P2WPKH), 10.0);
+    let with_change = total_spend_cost(1_410, 25_000, spend_later);
+    assert_eq!(with_change, 1_410 + 680);
+
+    // Dropping change pays more now but nothing later
+    let without_change = total_spend_cost(1_100, 0, spend_later);
+    assert_eq!(without_change, 1_100);
+    assert!(without_change < with_change);
+}
+
+#[test]
+fn test_normalize_address() {
+    assert_eq!(
+        address::normalize_address("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4").unwrap(),
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+    );
+    assert_eq!(
+        address::normalize_address(" 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa ").unwrap(),
+        "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"
+    );
+
+    // Mixed case is invalid Bech32, and a bad checksum is invalid Base58Check
+    assert!(matches!(
+        address::normalize_address("bc1QW508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+        Err(BitcoinError::ParseError(_))
+    ));
+    assert!(address::normalize_address("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb").is_err());
+}
+
+#[test]
+fn test_bip69_reorder_map() {
+    let input = |first: u8, last: u8, vout: u32| {
+        let mut txid = [0x00; 32];
+        txid[0] = first;
+        txid[31] = last;
+        TxInput {
+            previous_output: OutPoint { txid, vout },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFF,
+        }
+    };
+    let output = |value: u64, hash: u8| TxOutput {
+        value,
+        script_pubkey: script::p2pkh_script(&[hash; 20]),
+    };
+
+    // Txids compare from their last internal byte, as they are displayed
+    let tx = LegacyTransaction::builder()
+        .add_input(input(0x01, 0x02, 0))
+        .add_input(input(0xff, 0x01, 1))
+        .add_input(input(0xff, 0x01, 0))
+        .add_output(output(5_000, 0x22))
+        .add_output(output(1_000, 0x33))
+        .add_output(output(5_000, 0x11))
+        .build();
+    assert_eq!(tx.bip69_reorder_map(), (vec![2, 1, 0], vec![1, 2, 0]));
+
+    let sorted = LegacyTransaction::builder()
+        .add_input(input(0x00, 0x01, 0))
+        .add_output(output(1_000, 0x11))
+        .add_output(output(2_000, 0x11))
+        .build();
+    assert_eq!(sorted.bip69_reorder_map(), (vec![0], vec![0, 1]));
+}
+
+#[test]
+fn test_validate_batch() {
+    let mut duplicate = sample_unsigned_tx();
+    duplicate.inputs.push(duplicate.inputs[0].clone());
+    let mut batch = vec![coinbase_tx(&[1_000]); 10];
+    batch.push(sample_unsigned_tx());
+    batch.insert(7, duplicate);
+
+    let results = validate_batch(&batch);
+    assert_eq!(results.len(), batch.len());
+    let failures: Vec<usize> = (0..results.len())
+        .filter(|&i| results[i].is_err())
+        .collect();
+    assert_eq!(failures, [7]);
+    assert!(matches!(results[7], Err(BitcoinError::InvalidTransaction)));
+
+    let mut too_much = sample_unsigned_tx();
+    too_much.outputs[0].value = block::MAX_MONEY + 1;
+    assert!(matches!(
+        too_much.check_structure(),
+        Err(BitcoinError::InvalidAmount)
+    ));
+    assert!(validate_batch(&[]).is_empty());
+}
+
+#[test]
+fn test_format_fiat() {
+    assert_eq!(format_fiat(100_000_000, 61_234.5), "61234.50");
+    assert_eq!(format_fiat(250_000, 60_000.0), "150.00");
+    // A single satoshi is worth less than a cent
+    assert_eq!(format_fiat(1, 60_000.0), "0.00");
+    assert_eq!(format_fiat(10, 60_000.0), "0.01");
+}
+
+#[test]
+fn test_valid_retarget() {
+    // Mainnet retarget at height 32,256
+    assert!(valid_retarget(0x1d00ffff, 1_022_578, 0x1d00d86a));
+    assert!(!valid_retarget(0x1d00ffff, 1_022_578, 0x1d00d86b));
+
+    // Slow periods never ease past the proof-of-work limit
+    assert!(valid_retarget(0x1d00ffff, 2_055_491, 0x1d00ffff));
+
+    // A fast period is clamped to a quarter of the target timespan
+    assert!(valid_retarget(0x1c05a3f4, 289_434, 0x1c0168fd));
+    assert!(valid_retarget(0x1c05a3f4, 1, 0x1c0168fd));
+
+    // A slow period is clamped to four times the target timespan
+    assert!(valid_retarget(0x1c387f6f, 6_048_000, 0x1d00e1fd));
+    assert!(valid_retarget(0x1c387f6f, u32::MAX, 0x1d00e1fd));
+
+    // Negative targets are invalid
+    assert!(!valid_retarget(
+        0x1d80ffff,
+        block::TARGET_TIMESPAN,
+        0x1d00ffff
+    ));
+}
+
+#[test]
+fn test_timelock_condition() {
+    let cltv = |locktime: &[u8]| TxOutput {
+        value: 50_000,
+        script_pubkey: [
+            vec![locktime.len() as u8],
+            locktime.to_vec(),
+            vec![0xb1, 0x75],
+            script::p2pkh_script(&[0x55; 20]),
+        ]
+        .concat(),
+    };
+
+    // Height 800,000 and a 5-byte timestamp whose top bit would read as a sign
+    assert_eq!(
+        cltv(&[0x00, 0x35, 0x0c]).timelock_condition(),
+        Some(LockTime::Blocks(800_000))
+    );
+    assert_eq!(
+        cltv(&[0x00, 0x00, 0x00, 0x80, 0x00]).timelock_condition(),
+        Some(LockTime::Timestamp(0x8000_0000))
+    );
+
+    // Negative locktimes always fail, and plain scripts carry no timelock
+    assert_eq!(cltv(&[0x81]).timelock_condition(), None);
+    let p2pkh = TxOutput {
+        value: 50_000,
+        script_pubkey: script::p2pkh_script(&[0x55; 20]),
+    };
+    assert_eq!(p2pkh.timelock_condition(), None);
+}
+
+#[test]
+fn test_change_reuses_script() {
+    let recipient = script::witness_script(0, &[0x44; 20]);
+    let used = script::p2pkh_script(&[0x55; 20]);
+    let fresh = script::witness_script(0, &[0x66; 20]);
+    let recipients = [recipient.clone()];
+    let history = [used.clone()];
+
+    assert!(change_reuses_script(&recipient, &recipients, &history));
+    assert!(change_reuses_script(&used, &recipients, &history));
+    assert!(!change_reuses_script(&fresh, &recipients, &history));
+}
+
+#[test]
+fn test_signing_summary() {
+    let wallet_spk = script::witness_script(0, &[0x33; 20]);
+    let tx = LegacyTransaction::builder()
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout: 0,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFD,
+        })
+        .add_output(TxOutput {
+            value: 50_000,
+            script_pubkey: script::witness_script(0, &[0x22; 20]),
+        })
+        .add_output(TxOutput {
+            value: 48_500,
+            script_pubkey: wallet_spk.clone(),
+        })
+        .build();
+    let prevouts = [TxOutput {
+        value: 100_000,
+        script_pubkey: wallet_spk,
+    }];
+
+    let summary = tx.signing_summary(&prevouts, Network::Mainnet).unwrap();
+    assert_eq!(
+        summary,
+        format!(
+            "Send 50,000 sat to {}, fee 1,500 sat, change 48,500 sat",
+            address::p2wpkh_address(&[0x22; 20], &Network::Mainnet)
+        )
+    );
+
+    assert!(matches!(
+        tx.signing_summary(&[], Network::Mainnet),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_min_fee_bump_increment() {
+    assert_eq!(min_fee_bump_increment(141, MIN_RELAY_FEERATE), 141);
+    assert_eq!(min_fee_bump_increment(141, 0.1), 15);
+    assert_eq!(
+        bip125_replacement_requirements(1_000, 141, 141, MIN_RELAY_FEERATE),
+        1_000 + min_fee_bump_increment(141, MIN_RELAY_FEERATE)
+    );
+}
+
+#[test]
+fn test_validate_all() {
+    let mut broken = sample_unsigned_tx();
+    broken.inputs.push(broken.inputs[0].clone());
+    broken.outputs.push(TxOutput {
+        value: 100,
+        script_pubkey: script::p2pkh_script(&[0x55; 20]),
+    });
+    broken.outputs.push(TxOutput {
+        value: 10_000,
+        script_pubkey: vec![0xff, 0xff],
+    });
+
+    let messages = |tx: &LegacyTransaction| -> Vec<String> {
+        validate_all(tx)
+            .into_iter()
+            .map(|error| match error {
+                BitcoinError::ParseError(message) => message,
+                other => panic!("expected a described problem, got {other:?}"),
+            })
+            .collect()
+    };
+    assert_eq!(
+        messages(&broken),
+        [
+            "input 1: spends the same outpoint as input 0",
+            "output 1: value 100 below dust threshold 546",
+            "output 2: non-standard script",
+        ]
+    );
+
+    let empty = LegacyTransaction::builder().build();
+    assert_eq!(messages(&empty), ["no inputs", "no outputs"]);
+    assert!(validate_all(&sample_unsigned_tx()).is_empty());
+}
+
+#[test]
+fn test_encoding_efficiency() {
+    // 1-in 2-out P2PKH spend versus the same spend as P2WPKH
+    assert!((encoding_efficiency(226, 141) - 0.376_106).abs() < 1e-6);
+    assert_eq!(encoding_efficiency(200, 150), 0.25);
+    assert_eq!(encoding_efficiency(150, 150), 0.0);
+    assert_eq!(encoding_efficiency(0, 150), 0.0);
+}
+
+#[test]
+fn test_cli_fee() {
+    let raw = hex::encode(&sample_unsigned_tx().to_bytes());
+    let vsize = sample_unsigned_tx().vsize();
+    assert_eq!(vsize, 82);
+
+    assert_eq!(
+        fee_output(&raw, 12.5).unwrap(),
+        "vsize: 82 vB\nfee rate: 12.5 sat/vB\nfee: 1,025 sat\n"
+    );
+
+    let args = vec!["fee".to_string(), raw.clone(), "12.5".to_string()];
+    assert!(matches!(
+        parse_cli_args(&args),
+        Ok(CliCommand::Fee { feerate, .. }) if feerate == 12.5
+    ));
+
+    let args = vec!["fee".to_string(), "zz".to_string(), "1".to_string()];
+    assert!(matches!(
+        parse_cli_args(&args),
+        Err(BitcoinError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_short_txid() {
+    // Reference vector from the SipHash paper
+    let key: Vec<u8> = (0..16).collect();
+    let k0 = u64::from_le_bytes(key[..8].try_into().unwrap());
+    let k1 = u64::from_le_bytes(key[8..].try_into().unwrap());
+    let message: Vec<u8> = (0..15).collect();
+    assert_eq!(hashes::siphash24(k0, k1, &message), 0xa129_ca61_49be_45e5);
+
+    // Genesis coinbase in a compact block of the genesis header
+    let genesis = hex::decode(
+        "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+    )
+    .unwrap();
+    let header = BlockHeader::from_bytes(&genesis).unwrap();
+    let short_id = block::short_txid(&header, 0x0123_4567_89ab_cdef, &header.merkle_root);
+    assert_eq!(short_id, 0xd97c_3183_bddd);
+    assert!(short_id < 1 << 48);
+}
+
+#[test]
+fn test_block_total_fees() {
+    // Height 840,000 is the fourth halving: 3.125 BTC subsidy
+    assert_eq!(
+        block_total_fees(312_500_000 + 13_000_000, 840_000).unwrap(),
+        13_000_000
+    );
+    assert_eq!(block_total_fees(312_500_000, 840_000).unwrap(), 0);
+    assert!(matches!(
+        block_total_fees(312_499_999, 840_000),
+        Err(BitcoinError::InvalidAmount)
+    ));
+}
+
+#[test]
+fn test_bumpable() {
+    let tx = |sequence: u32, change: u64| {
+        LegacyTransaction::builder()
+            .add_input(TxInput {
+                previous_output: OutPoint {
+                    txid: [0x11; 32],
+                    vout: 0,
+                },
+                script_sig: vec![],
+                sequence,
+            })
+            .add_output(TxOutput {
+                value: 5_000_000,
+                script_pubkey: script::witness_script(0, &[0x44; 20]),
+            })
+            .add_output(TxOutput {
+                value: change,
+                script_pubkey: script::p2pkh_script(&[0x55; 20]),
+            })
+            .build()
+    };
+
+    assert!(tx(0xFFFFFFFD, 1_234_567).bumpable());
+    // No RBF signal, or change already at the dust limit
+    assert!(!tx(0xFFFFFFFF, 1_234_567).bumpable());
+    assert!(!tx(0xFFFFFFFD, 543).bumpable());
+}
+
+#[test]
+fn test_wire_format_endianness() {
+    let mut txid = [0u8; 32];
+    txid[0] = 0xaa;
+    txid[31] = 0xbb;
+    let tx = LegacyTransaction::builder()
+        .version(0x0102_0304)
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid,
+                vout: 0x0a0b_0c0d,
+            },
+            script_sig: vec![0x51],
+            sequence: 0x1122_3344,
+        })
+        .add_output(TxOutput {
+            value: 0x0102_0304_0506_0708,
+            script_pubkey: vec![0x6a],
+        })
+        .lock_time(0x5566_7788)
+        .build();
+    let bytes = tx.to_bytes();
+
+    assert_eq!(bytes.len(), 4 + 1 + 32 + 4 + 2 + 4 + 1 + 8 + 2 + 4);
+    assert_eq!(bytes[0..4], [0x04, 0x03, 0x02, 0x01]); // version
+    assert_eq!(bytes[4], 1); // input count
+    assert_eq!(bytes[5], 0xaa); // txid in internal order, not reversed
+    assert_eq!(bytes[36], 0xbb);
+    assert_eq!(bytes[37..41], [0x0d, 0x0c, 0x0b, 0x0a]); // vout
+    assert_eq!(bytes[41..43], [0x01, 0x51]); // scriptSig
+    assert_eq!(bytes[43..47], [0x44, 0x33, 0x22, 0x11]); // sequence
+    assert_eq!(bytes[47], 1); // output count
+    assert_eq!(
+        bytes[48..56],
+        [0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]
+    ); // value
+    assert_eq!(bytes[56..58], [0x01, 0x6a]); // scriptPubKey
+    assert_eq!(bytes[58..62], [0x88, 0x77, 0x66, 0x55]); // lock_time
+
+    let parsed = LegacyTransaction::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.version, 0x0102_0304);
+    assert_eq!(parsed.inputs[0].previous_output.vout, 0x0a0b_0c0d);
+    assert_eq!(parsed.inputs[0].sequence, 0x1122_3344);
+    assert_eq!(parsed.outputs[0].value, 0x0102_0304_0506_0708);
+    assert_eq!(parsed.lock_time, 0x5566_7788);
+}
+
+#[test]
+fn test_witness_sizes() {
+    let input = |vout: u32| TxInput {
+        previous_output: OutPoint {
+            txid: [1; 32],
+            vout,
+        },
+        script_sig: vec![],
+        sequence: 0xFFFFFFFF,
+    };
+    let tx = SegwitTransaction {
+        version: 2,
+        inputs: vec![input(0), input(1)],
+        outputs: vec![],
+        witnesses: vec![vec![vec![0x30; 71], vec![0x02; 33]]],
+        lock_time: 0,
+    };
+
+    // Count byte plus two length-prefixed items; a lone count byte for no witness
+    let sizes = tx.witness_sizes();
+    assert_eq!(sizes, [1 + 1 + 71 + 1 + 33, 1]);
+    assert_eq!(sizes.iter().sum::<usize>(), tx.serialize_witnesses().len());
+}
+
+#[test]
+fn test_error_context() {
+    let error = LegacyTransaction::from_bytes(&[0x01, 0x00])
+        .unwrap_err()
+        .context("input 3")
+        .context("block 840000");
+    let BitcoinError::ParseError(message) = &error else {
+        panic!("expected a parse error");
+    };
+    assert!(message.starts_with("block 840000: input 3: "));
+    assert!(
+        error
+            .to_string()
+            .starts_with("Parse error: block 840000: input 3: ")
+    );
+
+    assert!(matches!(
+        BitcoinError::InvalidAmount.context("output 0"),
+        BitcoinError::InvalidAmount
+    ));
+}
+
+#[test]
+fn test_is_cpfp_package() {
+    let parent = sample_unsigned_tx();
+    let spend = |txid: [u8; 32]| {
+        LegacyTransaction::builder()
+            .add_input(TxInput {
+                previous_output: OutPoint { txid, vout: 0 },
+                script_sig: vec![],
+                sequence: 0xFFFFFFFD,
+            })
+            .add_output(TxOutput {
+                value: 80_000,
+                script_pubkey: script::p2pkh_script(&[0x55; 20]),
+            })
+            .build()
+    };
+
+    assert!(is_cpfp_package(&parent, &spend(parent.txid())));
+    assert!(!is_cpfp_package(&parent, &spend([0x99; 32])));
+    assert!(!is_cpfp_package(&spend(parent.txid()), &parent));
+}
+
+#[test]
+fn test_cpfp_package_feerate() {
+    // Parent pays 82 sat for 82 vB, 1 sat/vB
+    let parent = sample_unsigned_tx();
+    let parent_values = [90_082];
+    assert_eq!(parent.fee(&parent_values).unwrap(), 82);
+
+    let child = LegacyTransaction::builder()
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: parent.txid(),
+                vout: 0,
+            },
+            script_sig: vec![],
+            sequence: 0xFFFFFFFD,
+        })
+        .add_output(TxOutput {
+            value: 90_000 - 4_838,
+            script_pubkey: script::witness_script(0, &[0x33; 20]),
+        })
+        .build();
+    assert_eq!(child.vsize(), 82);
+
+    // 4,920 sat over 164 vB
+    let rate = cpfp_package_feerate(&parent, &parent_values, &child, &[90_000]).unwrap();
+    assert_eq!(rate, 30.0);
+
+    assert!(matches!(
+        cpfp_package_feerate(&parent, &[], &child, &[90_000]),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+    assert!(matches!(
+        cpfp_package_feerate(&parent, &[1_000], &child, &[90_000]),
+        Err(BitcoinError::InvalidAmount)
+    ));
+
+    // Swapped roles: the parent spends nothing of the child
+    assert!(matches!(
+        cpfp_package_feerate(&child, &[90_000], &parent, &parent_values),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_btc_string_to_sats() {
+    // None of these land on a whole satoshi via f64 multiplication
+    assert_eq!(btc_string_to_sats("0.1").unwrap(), 10_000_000);
+    assert_eq!(btc_string_to_sats("0.29").unwrap(), 29_000_000);
+    assert_eq!((0.29 * 100_000_000.0) as u64, 28_999_999);
+    assert_eq!(
+        btc_string_to_sats("20999999.97690000").unwrap(),
+        2_099_999_997_690_000
+    );
+
+    assert_eq!(btc_string_to_sats("21").unwrap(), 2_100_000_000);
+    assert_eq!(btc_string_to_sats(".00000001").unwrap(), 1);
+    assert_eq!(btc_string_to_sats("1.").unwrap(), 100_000_000);
+
+    assert!(matches!(
+        btc_string_to_sats("0.000000001"),
+        Err(BitcoinError::InvalidAmount)
+    ));
+    assert!(matches!(
+        btc_string_to_sats("184467440738"),
+        Err(BitcoinError::InvalidAmount)
+    ));
+    for bad in ["", ".", "-1", "1.2.3", "1e8", "0x10"] {
+        assert!(matches!(
+            btc_string_to_sats(bad),
+            Err(BitcoinError::ParseError(_))
+        ));
+    }
+}
+
+#[test]
+fn test_builder_config_hash() {
+    let builder = || {
+        LegacyTransaction::builder()
+            .version(2)
+            .add_input(TxInput {
+                previous_output: OutPoint {
+                    txid: [0x11; 32],
+                    vout: 0,
+                },
+                script_sig: vec![],
+                sequence: 0xFFFFFFFD,
+            })
+            .add_output(TxOutput {
+                value: 90_000,
+                script_pubkey: script::witness_script(0, &[0x22; 20]),
+            })
+    };
+
+    assert_eq!(builder().config_hash(), builder().config_hash());
+    assert_eq!(
+        builder().config_hash(),
+        hashes::siphash24(0, 0, &builder().build().to_bytes())
+    );
+    assert_ne!(
+        builder().config_hash(),
+        builder().lock_time(1).config_hash()
+    );
+    assert_ne!(builder().config_hash(), builder().version(1).config_hash());
+
+    // Builders differing only in prevout amounts hash differently
+    let with_values = builder().with_input_values(vec![100_000]).config_hash();
+    assert_ne!(with_values, builder().config_hash());
+    assert_ne!(
+        with_values,
+        builder().with_input_values(vec![95_000]).config_hash()
+    );
+    assert_eq!(
+        with_values,
+        builder().with_input_values(vec![100_000]).config_hash()
+    );
+}
+
+#[test]
+fn test_compact_size_core() {
+    let buffer = [0xfe, 0x78, 0x56, 0x34, 0x12, 0xaa];
+    assert_eq!(varint::decode_compact_size(&buffer), Ok((0x1234_5678, 5)));
+    assert_eq!(varint::decode_compact_size(&[0x2a]), Ok((42, 1)));
+    assert_eq!(
+        varint::decode_compact_size(&buffer[..3]),
+        Err(varint::CompactSizeError::Truncated)
+    );
+    assert_eq!(
+        varint::decode_compact_size(&[0xfd, 0x10, 0x00]),
+        Err(varint::CompactSizeError::NonCanonical)
+    );
+
+    let mut out = [0; varint::MAX_COMPACT_SIZE_LEN];
+    assert_eq!(varint::encode_compact_size(0x1234_5678, &mut out), 5);
+    assert_eq!(out[..5], buffer[..5]);
+    assert_eq!(varint::encode_compact_size(u64::MAX, &mut out), 9);
+    assert_eq!(varint::decode_compact_size(&out), Ok((u64::MAX, 9)));
+}
+
+#[test]
+fn test_block_io_totals() {
+    let mut spend = sample_unsigned_tx();
+    spend.inputs.push(spend.inputs[0].clone());
+    spend.outputs.push(spend.outputs[0].clone());
+    let block = sample_block(vec![coinbase_tx(&[625_000_000, 0, 1]), spend]);
+    assert_eq!(block.io_totals(), (3, 5));
+    assert_eq!(sample_block(vec![]).io_totals(), (0, 0));
+}
+
+#[test]
+fn test_unusual_output_count() {
+    let mut tx = sample_unsigned_tx();
+    tx.outputs.push(TxOutput {
+        value: 10_000,
+        script_pubkey: script::p2pkh_script(&[0x55; 20]),
+    });
+    assert!(!tx.unusual_output_count(2));
+
+    // The receiver contributed an extra output
+    tx.outputs.push(TxOutput {
+        value: 20_000,
+        script_pubkey: script::witness_script(0, &[0x66; 20]),
+    });
+    assert!(tx.unusual_output_count(2));
+}
+
+#[test]
+fn test_serialize_body() {
+    let tx = LegacyTransaction::builder()
+        .version(2)
+        .add_input(sample_unsigned_tx().inputs[0].clone())
+        .add_output(sample_unsigned_tx().outputs[0].clone())
+        .lock_time(840_000)
+        .build();
+
+    let header = tx.serialize();
+    let body = tx.serialize_body();
+    assert_eq!(body[0], 1);
+    assert_eq!(
+        [&header[..4], &body[..], &header[4..]].concat(),
+        tx.to_bytes()
+    );
+}
+
+#[test]
+fn test_max_feerate_from_change() {
+    assert_eq!(max_feerate_from_change(14_646, 141, 546), 100.0);
+    assert_eq!(max_feerate_from_change(28_746, 141, 546), 200.0);
+    assert!(max_feerate_from_change(50_000, 141, 546) > max_feerate_from_change(40_000, 141, 546));
+    assert_eq!(max_feerate_from_change(500, 141, 546), 0.0);
+    assert_eq!(max_feerate_from_change(14_646, 0, 546), 0.0);
+}
+
+#[test]
+fn test_builder_finalize_input_values() {
+    let builder = |output_value: u64| {
+        LegacyTransaction::builder()
+            .add_input(sample_unsigned_tx().inputs[0].clone())
+            .add_output(TxOutput {
+                value: output_value,
+                script_pubkey: script::p2pkh_script(&[0x55; 20]),
+            })
+    };
+
+    let tx = builder(99_000)
+        .with_input_values(vec![100_000])
+        .finalize()
+        .unwrap();
+    assert_eq!(tx.fee(&[100_000]).unwrap(), 1_000);
+
+    assert!(matches!(
+        builder(100_001).with_input_values(vec![100_000]).finalize(),
+        Err(BitcoinError::InvalidAmount)
+    ));
+    assert!(matches!(
+        builder(1_000).with_input_values(vec![]).finalize(),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+    // Without recorded values there is nothing to check
+    assert!(builder(100_001).finalize().is_ok());
+}
+
+#[test]
+fn test_round_output_ratio() {
+    let mut tx = sample_unsigned_tx();
+    tx.outputs.push(TxOutput {
+        value: 1_234_567,
+        script_pubkey: script::p2pkh_script(&[0x55; 20]),
+    });
+    assert_eq!(tx.round_output_ratio(), 0.5);
+
+    tx.outputs[1].value = 200_000_000;
+    assert_eq!(tx.round_output_ratio(), 1.0);
+    assert_eq!(
+        LegacyTransaction::builder().build().round_output_ratio(),
+        0.0
+    );
+}
+
+#[test]
+fn test_collect_prevout_scripts() {
+    let tx = sample_unsigned_tx();
+    let prevout = TxOutput {
+        value: 100_000,
+        script_pubkey: script::p2pkh_script(&[0x33; 20]),
+    };
+
+    let scripts = tx
+        .collect_prevout_scripts(std::slice::from_ref(&prevout))
+        .unwrap();
+    assert_eq!(scripts, [script::p2pkh_script(&[0x33; 20])]);
+    assert_eq!(
+        tx.signing_requests(&scripts, script::SIGHASH_ALL as u32)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    assert!(matches!(
+        tx.collect_prevout_scripts(&[prevout.clone(), prevout]),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_to_broadcast_hex() {
+    let tx = sample_unsigned_tx();
+    assert_eq!(tx.to_broadcast_hex().unwrap(), hex::encode(&tx.to_bytes()));
+
+    let segwit = SegwitTransaction {
+        version: tx.version,
+        inputs: tx.inputs.clone(),
+        outputs: tx.outputs.clone(),
+        witnesses: vec![vec![vec![0x30; 71], vec![0x02; 33]]],
+        lock_time: 0,
+    };
+    let segwit_hex = segwit.to_broadcast_hex().unwrap();
+    assert_eq!(&segwit_hex[8..12], "0001");
+    assert_eq!(segwit_hex, hex::encode(&segwit.to_bytes()));
+
+    let mut no_outputs = tx.clone();
+    no_outputs.outputs.clear();
+    assert!(matches!(
+        no_outputs.to_broadcast_hex(),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+    let mut overflow = tx;
+    overflow.outputs[0].value = u64::MAX;
+    assert!(matches!(
+        overflow.to_broadcast_hex(),
+        Err(BitcoinError::InvalidAmount)
+    ));
+}
+
+#[test]
+fn test_psbt_estimated_final_vsize() {
+    let tx = sample_unsigned_tx();
+    let mut psbt = Psbt::from_unsigned_tx(&tx);
+    assert!(matches!(
+        psbt.estimated_final_vsize(),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+
+    psbt.set_witness_utxo(
+        0,
+        &TxOutput {
+            value: 100_000,
+            script_pubkey: script::witness_script(0, &[0x33; 20]),
+        },
+    )
+    .unwrap();
+    // Overhead 10 + marker/flag 1 + P2WPKH input 68 + P2WPKH output 31
+    assert_eq!(psbt.estimated_final_vsize().unwrap(), 110);
+
+    let signed = SegwitTransaction {
+        version: tx.version,
+        inputs: tx.inputs.clone(),
+        outputs: tx.outputs.clone(),
+        witnesses: vec![vec![vec![0x30; 72], vec![0x02; 33]]],
+        lock_time: tx.lock_time,
+    };
+    assert!(signed.vsize().abs_diff(110) <= 1);
+}
+
+#[test]
+fn test_output_value_histogram() {
+    let mut builder = LegacyTransaction::builder();
+    for (i, value) in [1_000_000, 37_512, 1_000_000, 1_000_000, 12_345, 1_000_000]
+        .into_iter()
+        .enumerate()
+    {
+        builder = builder.add_output(TxOutput {
+            value,
+            script_pubkey: script::witness_script(0, &[i as u8; 20]),
+        });
+    }
+    let tx = builder.build();
+
+    assert_eq!(
+        tx.output_value_histogram(),
+        [(12_345, 1), (37_512, 1), (1_000_000, 4)]
+    );
+    assert!(
+        LegacyTransaction::builder()
+            .build()
+            .output_value_histogram()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_version_warnings() {
+    let mut tx = sample_unsigned_tx();
+    tx.version = 1;
+    tx.inputs[0].sequence = 144;
+
+    let warnings = tx.version_warnings();
+    assert_eq!(
+        warnings,
+        ["input 0 sets relative timelock 144 blocks, which version 1 transactions do not enforce"]
+    );
+
+    tx.version = 2;
+    assert!(tx.version_warnings().is_empty());
+
+    tx.version = 7;
+    assert_eq!(
+        tx.version_warnings(),
+        ["version 7 is non-standard; use 1 to 3"]
+    );
+}
+
+#[test]
+fn test_merkle_accumulator() {
+    let txids: Vec<[u8; 32]> = (0..37u8).map(|i| hashes::sha256(&[i])).collect();
+
+    let mut accumulator = merkle::MerkleAccumulator::new();
+    assert_eq!(accumulator.root(), None);
+    for (count, txid) in txids.iter().enumerate() {
+        accumulator.add(*txid);
+        assert_eq!(
+            accumulator.root(),
+            merkle::merkle_root(&txids[..=count]),
+            "{} txids",
+            count + 1
+        );
+    }
+}
+
+#[test]
+fn test_overpayment_warning() {
+    // 82 vB paying 4,100 sat is 50 sat/vB
+    let tx = sample_unsigned_tx();
+    let input_values = [94_100];
+
+    assert_eq!(
+        tx.overpayment_warning(&input_values, 10.0, 3.0).unwrap(),
+        "fee rate 50.0 sat/vB is 5.0x the recommended 10.0 sat/vB (4,100 sat)"
+    );
+    assert_eq!(tx.overpayment_warning(&input_values, 10.0, 5.0), None);
+    assert_eq!(tx.overpayment_warning(&[], 10.0, 3.0), None);
+}
+
+#[test]
+fn test_proof_of_reserves() {
+    let message = "Reserves of Example Exchange, 2026-10-14";
+    let input = |txid: [u8; 32], vout: u32| TxInput {
+        previous_output: OutPoint { txid, vout },
+        script_sig: vec![],
+        sequence: 0xFFFFFFFF,
+    };
+    let proof = LegacyTransaction::builder()
+        .add_input(input(proof_of_reserves_commitment(message), 0))
+        .add_input(input([0x11; 32], 0))
+        .add_input(input([0x22; 32], 3))
+        .add_output(TxOutput {
+            value: 3_000_000,
+            script_pubkey: script::op_return_script(b"").unwrap(),
+        })
+        .build();
+
+    assert!(proof.is_proof_of_reserves());
+    assert!(proof.proves_reserves_for(message));
+    assert!(!proof.proves_reserves_for("another message"));
+
+    // An ordinary payment lacks the challenge input shape
+    assert!(!sample_unsigned_tx().is_proof_of_reserves());
+    let mut two_outputs = proof.clone();
+    two_outputs.outputs.push(two_outputs.outputs[0].clone());
+    assert!(!two_outputs.is_proof_of_reserves());
+}
+
+#[test]
+fn test_cumulative_output_values() {
+    let mut tx = sample_unsigned_tx();
+    for value in [10_000, 2_500] {
+        tx.outputs.push(TxOutput {
+            value,
+            script_pubkey: script::p2pkh_script(&[0x55; 20]),
+        });
+    }
+    assert_eq!(tx.cumulative_output_values(), [90_000, 100_000, 102_500]);
+    assert_eq!(
+        *tx.cumulative_output_values().last().unwrap(),
+        tx.total_output_value().unwrap()
+    );
+}
+
+#[test]
+fn test_looks_like_ln_commitment() {
+    // Obscured commitment number 0x123456_789abc
+    let commitment = LegacyTransaction::builder()
+        .version(2)
+        .add_input(TxInput {
+            previous_output: OutPoint {
+                txid: [0x11; 32],
+                vout: 0,
+            },
+            script_sig: vec![],
+            sequence: 0x8012_3456,
+        })
+        .add_output(TxOutput {
+            value: 600_000,
+            script_pubkey: script::witness_script(0, &[0x22; 32]),
+        })
+        .add_output(TxOutput {
+            value: 390_000,
+            script_pubkey: script::witness_script(0, &[0x33; 20]),
+        })
+        .lock_time(0x2078_9abc)
+        .build();
+    assert!(commitment.looks_like_ln_commitment());
+
+    let mut ordinary = commitment.clone();
+    ordinary.lock_time = 840_000;
+    assert!(!ordinary.looks_like_ln_commitment());
+    let mut replaceable = commitment;
+    replaceable.inputs[0].sequence = 0xFFFFFFFD;
+    assert!(!replaceable.looks_like_ln_commitment());
+}
+
+#[test]
+fn test_anchor_outputs() {
+    let output = |value: u64, script_pubkey: Vec<u8>| TxOutput {
+        value,
+        script_pubkey,
+    };
+    let tx = LegacyTransaction::builder()
+        .add_output(output(330, script::witness_script(0, &[0x44; 32])))
+        .add_output(output(600_000, script::witness_script(0, &[0x22; 32])))
+        .add_output(output(330, script::witness_script(0, &[0x55; 20])))
+        .add_output(output(330, script::witness_script(0, &[0x66; 32])))
+        .build();
+
+    // The 330 sat P2WPKH output is not an anchor
+    assert_eq!(tx.anchor_outputs(), [0, 3]);
+    assert!(sample_unsigned_tx().anchor_outputs().is_empty());
+}
+
+#[test]
+fn test_size_profile() {
+    let tx = sample_unsigned_tx();
+    let profile = tx.size_profile();
+    assert_eq!(
+        profile,
+        SizeProfile {
+            overhead: 10,
+            inputs: 41,
+            outputs: 31,
+            witness: 0,
+        }
+    );
+    assert_eq!(profile.total(), tx.size());
+
+    let segwit = SegwitTransaction {
+        version: tx.version,
+        inputs: tx.inputs.clone(),
+        outputs: tx.outputs.clone(),
+        witnesses: vec![vec![vec![0x30; 71], vec![0x02; 33]]],
+        lock_time: tx.lock_time,
+    };
+    let profile = segwit.size_profile();
+    assert_eq!(profile.overhead, 12);
+    assert_eq!(profile.witness, 1 + 72 + 34);
+    assert_eq!(profile.total(), segwit.total_size());
+}
+
+#[test]
+fn test_verify_counts() {
+    let mut tx = sample_unsigned_tx();
+    tx.outputs.push(tx.outputs[0].clone());
+    let bytes = tx.to_bytes();
+    assert!(LegacyTransaction::verify_counts(&bytes).is_ok());
+
+    // Input count at byte 4, output count right after the 41-byte input
+    for (offset, count) in [(4, 0), (4, 3), (46, 1), (46, 5)] {
+        let mut corrupted = bytes.clone();
+        corrupted[offset] = count;
+        assert!(matches!(
+            LegacyTransaction::verify_counts(&corrupted),
+            Err(BitcoinError::InvalidTransaction)
+        ));
+    }
+
+    // Correct counts, but a trailing byte after the lock_time
+    let mut trailing = bytes.clone();
+    trailing.push(0x00);
+    assert!(matches!(
+        LegacyTransaction::verify_counts(&trailing),
+        Err(BitcoinError::InvalidTransaction)
+    ));
+}
+
+#[test]
+fn test_consolidation_savings() {
+    // 10 P2WPKH inputs are 680 vB: 3,400 sat at 5 sat/vB versus 13,600 at 20
+    assert_eq!(
+        consolidation_savings(10, ScriptType::P2WPKH, 5.0, 20.0),
+        10_200
+    );
+    assert!(consolidation_savings(10, ScriptType::P2WPKH, 20.0, 5.0) < 0);
+    assert_eq!(consolidation_savings(0, ScriptType::P2PKH, 1.0, 50.0), 0);
+}
+
+#[test]
+fn test_cli_dry_run_send() {
+    let args: Vec<String> = ["send", "1000", "address", "--dry-run"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let output = execute_cli_args(&args).unwrap();
+    assert!(output.dry_run);
+    assert!(matches!(
+        output.command,
+        CliCommand::Send { amount: 1000, .. }
+    ));
+    assert_eq!(
+        output.message,
+        "Dry run: would send 1000 satoshis to address\n"
+    );
+    assert!(!output.message.contains("Sending"));
+
+    // The flag is global, so it may also come before the subcommand
+    let args: Vec<String> = ["--dry-run", "send", "1000", "address"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    assert!(execute_cli_args(&args).unwrap().dry_run);
+
+    let args: Vec<String> = ["send", "1000", "address"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let output = execute_cli_args(&args).unwrap();
+    assert!(!output.dry_run);
+    assert_eq!(output.message, "Sending 1000 satoshis to address!\n");
+}
+
+#[test]
+fn test_txid_with_prefix_cache() {
+    let tx = sample_unsigned_tx();
+    let bytes = tx.to_bytes();
+
+    // Version-only prefix, and one spanning more than a whole SHA-256 block
+    for prefix_len in [4, bytes.len() - 10] {
+        let midstate = hashes::Sha256Midstate::new(&bytes[..prefix_len]);
+        assert_eq!(tx.txid_with_prefix_cache(&midstate), tx.txid());
+    }
+
+    // A prefix this transaction does not share falls back to the naive txid
+    let other = hashes::Sha256Midstate::new(&[0xff; 4]);
+    assert_eq!(tx.txid_with_prefix_cache(&other), tx.txid());
+}
+
+#[test]
+fn test_validate_input_script() {
+    let prevout = TxOutput {
+        value: 100_000,
+        script_pubkey: script::witness_script(0, &[0x22; 20]),
+    };
+    let input = sample_unsigned_tx().inputs[0].clone();
+    let mut pubkey = vec![0x02];
+    pubkey.extend([0x33; 32]);
+    let witness = vec![vec![0x30; 71], pubkey.clone()];
+    assert!(validate_input_script(&input, &witness, &prevout).is_ok());
+
+    // Missing witness, or the signature pushed in the scriptSig instead
+    assert!(matches!(
+        validate_input_script(&input, &[], &prevout),
+        Err(BitcoinError::InvalidScript)
+    ));
+    let mut legacy_style = input.clone();
+    script::push_data(&mut legacy_style.script_sig, &[0x30; 71]);
+    script::push_data(&mut legacy_style.script_sig, &pubkey);
+    assert!(matches!(
+        validate_input_script(&legacy_style, &witness, &prevout),
+        Err(BitcoinError::InvalidScript)
+    ));
+
+    // The same scriptSig is the right shape for a P2PKH prevout
+    let p2pkh = TxOutput {
+        value: 100_000,
+        script_pubkey: script::p2pkh_script(&[0x22; 20]),
+    };
+    assert!(validate_input_script(&legacy_style, &[], &p2pkh).is_ok());
+}